@@ -1,66 +1,676 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use chrono::{DateTime, Utc};
+use reqwest::StatusCode;
+use reqwest::header::{CONTENT_TYPE, HeaderMap, RETRY_AFTER};
+
+use serde::de::DeserializeOwned;
 use serde_json::Value;
 
-use crate::models::{BootstrapStatic, DreamTeam, LiveData, ManagerPicks, PlayerSummary};
+use crate::cache::{Cache, DEFAULT_TTL, FINISHED_TTL, LIVE_TTL};
+use crate::error::FplError;
+use crate::models::{BootstrapStatic, DreamTeam, Fixture, LiveData, ManagerPicks, PlayerSummary};
+
+pub const DEFAULT_API_BASE: &str = "https://fantasy.premierleague.com/api";
+
+const MAX_ATTEMPTS: u32 = 4;
+const BASE_RETRY_DELAY: Duration = Duration::from_millis(500);
+/// Longest delay we are willing to wait between attempts, whether computed
+/// or requested by the server through `Retry-After`.
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(10);
 
-pub struct FplClient;
+/// How long a response from a given endpoint stays fresh in the cache.
+#[derive(Clone, Copy)]
+enum Freshness {
+    Bootstrap,
+    Fixtures,
+    Event(u32),
+    ElementSummary,
+}
+
+/// Optional, more expensive parts of a snapshot.
+#[derive(Debug, Default)]
+pub struct SnapshotOptions {
+    /// Save every player's summary, one request per player.
+    pub players: bool,
+    /// Managers whose picks are saved for every started gameweek.
+    pub managers: Vec<u64>,
+}
+
+pub struct FplClient {
+    base_url: String,
+    client: reqwest::Client,
+    cache: Option<Cache>,
+    offline: Option<PathBuf>,
+}
+
+impl Default for FplClient {
+    fn default() -> Self {
+        Self::new(DEFAULT_API_BASE)
+    }
+}
 
 impl FplClient {
-    pub async fn fetch_bootstrap_static() -> Result<BootstrapStatic, Box<dyn std::error::Error>> {
-        let url = "https://fantasy.premierleague.com/api/bootstrap-static/";
-        let response = reqwest::get(url).await?;
-        let json: BootstrapStatic = response.json().await?;
-        Ok(json)
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            client: reqwest::Client::new(),
+            cache: None,
+            offline: None,
+        }
     }
 
-    pub async fn fetch_dream_team(event_id: u32) -> Result<DreamTeam, Box<dyn std::error::Error>> {
-        let url = format!(
-            "https://fantasy.premierleague.com/api/dream-team/{}/",
-            event_id
-        );
-        let response = reqwest::get(url).await?;
-        let json: DreamTeam = response.json().await?;
-        Ok(json)
+    pub fn with_cache(mut self, cache: Cache) -> Self {
+        self.cache = Some(cache);
+        self
     }
 
-    pub async fn fetch_fixtures() -> Result<Value, Box<dyn std::error::Error>> {
-        let url = "https://fantasy.premierleague.com/api/fixtures/";
-        let response = reqwest::get(url).await?;
-        let json: Value = response.json().await?;
-        Ok(json)
+    /// Read every response from a snapshot directory instead of the network.
+    pub fn with_offline(mut self, dir: PathBuf) -> Self {
+        self.offline = Some(dir);
+        self
     }
 
-    pub async fn fetch_live(event_id: u32) -> Result<LiveData, Box<dyn std::error::Error>> {
-        let url = format!(
-            "https://fantasy.premierleague.com/api/event/{}/live/",
-            event_id
-        );
-        let response = reqwest::get(url).await?;
-        let json: LiveData = response.json().await?;
-        Ok(json)
+    fn url(&self, path: &str) -> String {
+        format!("{}/{}", self.base_url, path)
+    }
+
+    fn ttl(&self, freshness: Freshness, body: &Value) -> Duration {
+        match freshness {
+            // `is_current` and `is_next` move on at the deadline, so the
+            // data must not outlive it.
+            Freshness::Bootstrap => {
+                if gameweek_in_progress(body) {
+                    LIVE_TTL
+                } else {
+                    until_next_deadline(body).map_or(DEFAULT_TTL, |until| until.min(DEFAULT_TTL))
+                }
+            }
+            Freshness::Fixtures => {
+                let in_play = body.as_array().is_some_and(|fixtures| {
+                    fixtures.iter().any(|f| {
+                        f["started"].as_bool() == Some(true)
+                            && f["finished"].as_bool() != Some(true)
+                    })
+                });
+                if in_play { LIVE_TTL } else { DEFAULT_TTL }
+            }
+            Freshness::Event(event_id) => {
+                let finished = self.cached_bootstrap().is_some_and(|bootstrap| {
+                    bootstrap["events"].as_array().is_some_and(|events| {
+                        events.iter().any(|e| {
+                            e["id"].as_u64() == Some(event_id as u64)
+                                && e["finished"].as_bool() == Some(true)
+                        })
+                    })
+                });
+                if finished { FINISHED_TTL } else { LIVE_TTL }
+            }
+            // Histories only gain a round once the next gameweek kicks
+            // off, so between gameweeks they stay fresh until its deadline.
+            Freshness::ElementSummary => match self.cached_bootstrap() {
+                Some(bootstrap) if gameweek_in_progress(&bootstrap) => LIVE_TTL,
+                Some(bootstrap) => until_next_deadline(&bootstrap)
+                    .map_or(DEFAULT_TTL, |until| until.min(FINISHED_TTL)),
+                None => LIVE_TTL,
+            },
+        }
+    }
+
+    fn cached_bootstrap(&self) -> Option<Value> {
+        self.cache.as_ref()?.peek(&self.url("bootstrap-static/"))
+    }
+
+    async fn get_value(&self, path: &str, freshness: Freshness) -> Result<Value, FplError> {
+        if let Some(dir) = &self.offline {
+            let file = snapshot_file(dir, path);
+            let content = fs::read(&file).map_err(|source| FplError::Offline {
+                path: path.to_string(),
+                file: file.clone(),
+                source,
+            })?;
+            return serde_json::from_slice(&content)
+                .map_err(|e| decode_error(&file.display().to_string(), e));
+        }
+
+        let url = self.url(path);
+        if let Some(body) = self.cache.as_ref().and_then(|cache| cache.get(&url)) {
+            return Ok(body);
+        }
+
+        let body = self.fetch_with_retry(&url).await?;
+        if let Some(cache) = &self.cache {
+            // The cache is best-effort: failing to write it must not fail the command.
+            let _ = cache.put(&url, &body, self.ttl(freshness, &body));
+        }
+        Ok(body)
+    }
+
+    /// Fetches `url`, retrying transient failures with exponential backoff
+    /// and jitter, or after the delay requested by `Retry-After`.
+    async fn fetch_with_retry(&self, url: &str) -> Result<Value, FplError> {
+        let mut attempt = 1;
+        loop {
+            let (error, retry_after) = match self.fetch_once(url).await {
+                Ok(body) => return Ok(body),
+                Err(failure) => failure,
+            };
+            if attempt >= MAX_ATTEMPTS || !error.is_transient() {
+                return Err(error);
+            }
+            let delay = match retry_after {
+                Some(delay) if delay > MAX_RETRY_DELAY => return Err(error),
+                Some(delay) => delay,
+                None => backoff_delay(attempt),
+            };
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    async fn fetch_once(&self, url: &str) -> Result<Value, (FplError, Option<Duration>)> {
+        let response = self
+            .client
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| (FplError::from(e), None))?;
+        let status = response.status();
+        let headers = response.headers().clone();
+        let retry_after = parse_retry_after(&headers);
+        let bytes = response
+            .bytes()
+            .await
+            .map_err(|e| (FplError::from(e), retry_after))?;
+
+        if is_game_updating(status, &headers, &bytes) {
+            return Err((FplError::GameUpdating, retry_after));
+        }
+        if !status.is_success() {
+            let error = FplError::Http {
+                status: status.as_u16(),
+                url: url.to_string(),
+            };
+            return Err((error, retry_after));
+        }
+        serde_json::from_slice(&bytes).map_err(|e| (decode_error(url, e), None))
+    }
+
+    async fn get_json<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        freshness: Freshness,
+    ) -> Result<T, FplError> {
+        let body = self.get_value(path, freshness).await?;
+        serde_path_to_error::deserialize(body).map_err(|e| FplError::Decode {
+            url: self.url(path),
+            path: e.path().to_string(),
+            message: e.inner().to_string(),
+        })
+    }
+
+    /// Saves bootstrap-static, fixtures and the live data and dream team of
+    /// every started gameweek into `dir`, plus whatever `options` opts into,
+    /// laid out so that [`FplClient::with_offline`] can read it back.
+    /// Returns the files written.
+    pub async fn save_snapshot(
+        &self,
+        dir: &Path,
+        options: &SnapshotOptions,
+    ) -> Result<Vec<PathBuf>, FplError> {
+        let mut written = Vec::new();
+        let bootstrap = self
+            .get_value("bootstrap-static/", Freshness::Bootstrap)
+            .await?;
+        written.push(write_snapshot_file(dir, "bootstrap-static/", &bootstrap)?);
+        let fixtures = self.get_value("fixtures/", Freshness::Fixtures).await?;
+        written.push(write_snapshot_file(dir, "fixtures/", &fixtures)?);
+
+        let started_events = bootstrap["events"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter(|e| {
+                e["finished"].as_bool() == Some(true) || e["is_current"].as_bool() == Some(true)
+            })
+            .filter_map(|e| e["id"].as_u64())
+            .collect::<Vec<_>>();
+        for &event_id in &started_events {
+            let live = format!("event/{}/live/", event_id);
+            let mut optional = vec![format!("dream-team/{}/", event_id)];
+            for manager_id in &options.managers {
+                optional.push(format!("entry/{}/event/{}/picks/", manager_id, event_id));
+            }
+            let freshness = Freshness::Event(event_id as u32);
+            let body = self.get_value(&live, freshness).await?;
+            written.push(write_snapshot_file(dir, &live, &body)?);
+            // A manager who joined mid-season has no picks for earlier
+            // gameweeks, so missing responses are skipped.
+            for path in optional {
+                match self.get_value(&path, freshness).await {
+                    Ok(body) => written.push(write_snapshot_file(dir, &path, &body)?),
+                    Err(FplError::Http { status: 404, .. }) => {}
+                    Err(e) => return Err(e),
+                }
+            }
+        }
+
+        if options.players {
+            let player_ids = bootstrap["elements"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|e| e["id"].as_u64());
+            for player_id in player_ids {
+                let path = format!("element-summary/{}/", player_id);
+                let body = self.get_value(&path, Freshness::ElementSummary).await?;
+                written.push(write_snapshot_file(dir, &path, &body)?);
+            }
+        }
+
+        Ok(written)
+    }
+
+    pub async fn fetch_bootstrap_static(&self) -> Result<BootstrapStatic, FplError> {
+        self.get_json("bootstrap-static/", Freshness::Bootstrap)
+            .await
+    }
+
+    pub async fn fetch_dream_team(&self, event_id: u32) -> Result<DreamTeam, FplError> {
+        self.get_json(
+            &format!("dream-team/{}/", event_id),
+            Freshness::Event(event_id),
+        )
+        .await
+    }
+
+    pub async fn fetch_fixtures(&self) -> Result<Vec<Fixture>, FplError> {
+        self.get_json("fixtures/", Freshness::Fixtures).await
+    }
+
+    pub async fn fetch_live(&self, event_id: u32) -> Result<LiveData, FplError> {
+        self.get_json(
+            &format!("event/{}/live/", event_id),
+            Freshness::Event(event_id),
+        )
+        .await
     }
 
     pub async fn fetch_manager_picks(
+        &self,
         manager_id: u64,
         event_id: u32,
-    ) -> Result<ManagerPicks, Box<dyn std::error::Error>> {
-        let url = format!(
-            "https://fantasy.premierleague.com/api/entry/{}/event/{}/picks/",
-            manager_id, event_id
+    ) -> Result<ManagerPicks, FplError> {
+        self.get_json(
+            &format!("entry/{}/event/{}/picks/", manager_id, event_id),
+            Freshness::Event(event_id),
+        )
+        .await
+    }
+
+    pub async fn fetch_player_summary(&self, player_id: u64) -> Result<PlayerSummary, FplError> {
+        self.get_json(
+            &format!("element-summary/{}/", player_id),
+            Freshness::ElementSummary,
+        )
+        .await
+    }
+}
+
+/// The maintenance notice is short, so longer JSON bodies are not scanned.
+const MAX_NOTICE_LENGTH: usize = 1024;
+
+/// FPL answers with a 503, an HTML page, or a bare "The game is being
+/// updated." string while the game is updating between gameweeks.
+fn is_game_updating(status: StatusCode, headers: &HeaderMap, body: &[u8]) -> bool {
+    if status == StatusCode::SERVICE_UNAVAILABLE {
+        return true;
+    }
+    let content_type = headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    if content_type.starts_with("application/json") && body.len() > MAX_NOTICE_LENGTH {
+        return false;
+    }
+    let text = String::from_utf8_lossy(body).to_lowercase();
+    if text.contains("the game is being updated") {
+        return true;
+    }
+    let is_html = content_type.starts_with("text/html") || text.trim_start().starts_with('<');
+    status.is_success() && is_html
+}
+
+/// `Retry-After` is either a number of seconds or an HTTP date.
+fn parse_retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    let secs = (date.with_timezone(&Utc) - Utc::now()).num_seconds().max(0);
+    Some(Duration::from_secs(secs as u64))
+}
+
+/// Exponential backoff with jitter: a random delay between half and all of
+/// `BASE_RETRY_DELAY * 2^(attempt - 1)`, capped at `MAX_RETRY_DELAY`.
+fn backoff_delay(attempt: u32) -> Duration {
+    let delay = BASE_RETRY_DELAY
+        .saturating_mul(1 << (attempt - 1).min(16))
+        .min(MAX_RETRY_DELAY);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.subsec_nanos());
+    let jitter = delay.mul_f64(f64::from(nanos % 1000) / 2000.0);
+    delay / 2 + jitter
+}
+
+fn decode_error(url: &str, e: serde_json::Error) -> FplError {
+    FplError::Decode {
+        url: url.to_string(),
+        path: String::new(),
+        message: e.to_string(),
+    }
+}
+
+/// Writes `body` to the snapshot file of `path` and returns the file.
+fn write_snapshot_file(dir: &Path, path: &str, body: &Value) -> Result<PathBuf, FplError> {
+    let file = snapshot_file(dir, path);
+    if let Some(parent) = file.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&file, serde_json::to_vec(body).map_err(io::Error::from)?)?;
+    Ok(file)
+}
+
+/// Maps an API path such as `event/3/live/` to `<dir>/event/3/live.json`.
+fn snapshot_file(dir: &Path, path: &str) -> PathBuf {
+    dir.join(format!("{}.json", path.trim_end_matches('/')))
+}
+
+/// Time left until the next gameweek's deadline, at least [`LIVE_TTL`] so
+/// a passed deadline in stale data is rechecked soon; `None` when there is
+/// no next gameweek.
+fn until_next_deadline(bootstrap: &Value) -> Option<Duration> {
+    let deadline: DateTime<Utc> = bootstrap["events"]
+        .as_array()?
+        .iter()
+        .find(|e| e["is_next"].as_bool() == Some(true))?["deadline_time"]
+        .as_str()?
+        .parse()
+        .ok()?;
+    Some(
+        (deadline - Utc::now())
+            .to_std()
+            .map_or(LIVE_TTL, |until| until.max(LIVE_TTL)),
+    )
+}
+
+fn gameweek_in_progress(bootstrap: &Value) -> bool {
+    bootstrap["events"].as_array().is_some_and(|events| {
+        events.iter().any(|e| {
+            e["is_current"].as_bool() == Some(true) && e["finished"].as_bool() != Some(true)
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    /// Serves `responses` as `(status, content type, body)` to consecutive
+    /// requests on a random local port and returns the base URL and a handle
+    /// yielding the request lines that were received.
+    fn serve(
+        responses: Vec<(u16, &'static str, &'static str)>,
+    ) -> (String, std::thread::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let handle = std::thread::spawn(move || {
+            let mut requests = Vec::new();
+            for (status, content_type, body) in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut buf = [0u8; 4096];
+                let n = stream.read(&mut buf).unwrap();
+                let request = String::from_utf8_lossy(&buf[..n]).to_string();
+                write!(
+                    stream,
+                    "HTTP/1.1 {} Status\r\nContent-Type: {}\r\nContent-Length: {}\r\nRetry-After: 0\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    content_type,
+                    body.len(),
+                    body
+                )
+                .unwrap();
+                requests.push(request.lines().next().unwrap_or_default().to_string());
+            }
+            requests
+        });
+        (format!("http://{}/api/", addr), handle)
+    }
+
+    /// Serves `body` once as JSON.
+    fn serve_once(body: &'static str) -> (String, std::thread::JoinHandle<Vec<String>>) {
+        serve(vec![(200, "application/json", body)])
+    }
+
+    #[test]
+    fn test_new_trims_trailing_slash() {
+        let client = FplClient::new("http://localhost:8080/api/");
+        assert_eq!(
+            client.url("bootstrap-static/"),
+            "http://localhost:8080/api/bootstrap-static/"
+        );
+    }
+
+    #[test]
+    fn test_is_game_updating() {
+        let mut html = HeaderMap::new();
+        html.insert(CONTENT_TYPE, "text/html; charset=utf-8".parse().unwrap());
+        let json = HeaderMap::new();
+
+        assert!(is_game_updating(
+            StatusCode::SERVICE_UNAVAILABLE,
+            &json,
+            b""
+        ));
+        assert!(is_game_updating(
+            StatusCode::OK,
+            &html,
+            b"<html>maintenance</html>"
+        ));
+        assert!(is_game_updating(
+            StatusCode::OK,
+            &json,
+            b"\"The game is being updated.\""
+        ));
+        assert!(!is_game_updating(StatusCode::OK, &json, br#"{"picks":[]}"#));
+        // Long JSON bodies are not scanned for the notice.
+        let mut typed_json = HeaderMap::new();
+        typed_json.insert(CONTENT_TYPE, "application/json".parse().unwrap());
+        let long = format!(
+            r#"{{"news":"the game is being updated","pad":"{}"}}"#,
+            " ".repeat(MAX_NOTICE_LENGTH)
+        );
+        assert!(!is_game_updating(
+            StatusCode::OK,
+            &typed_json,
+            long.as_bytes()
+        ));
+        assert!(!is_game_updating(
+            StatusCode::NOT_FOUND,
+            &json,
+            br#"{"detail":"Not found."}"#
+        ));
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(parse_retry_after(&headers), None);
+        headers.insert(RETRY_AFTER, "120".parse().unwrap());
+        assert_eq!(parse_retry_after(&headers), Some(Duration::from_secs(120)));
+        headers.insert(
+            RETRY_AFTER,
+            "Wed, 21 Oct 2015 07:28:00 GMT".parse().unwrap(),
+        );
+        assert_eq!(parse_retry_after(&headers), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn test_backoff_delay_is_bounded() {
+        for attempt in 1..10 {
+            let full = BASE_RETRY_DELAY
+                .saturating_mul(1 << (attempt - 1))
+                .min(MAX_RETRY_DELAY);
+            let delay = backoff_delay(attempt);
+            assert!(delay >= full / 2 && delay <= full);
+        }
+    }
+
+    #[test]
+    fn test_snapshot_file() {
+        assert_eq!(
+            snapshot_file(Path::new("snap"), "event/3/live/"),
+            Path::new("snap").join("event").join("3").join("live.json")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_offline_reads_snapshot() {
+        let dir = std::env::temp_dir().join(format!("fplr-test-offline-{}", std::process::id()));
+        let file = snapshot_file(&dir, "entry/1/event/2/picks/");
+        fs::create_dir_all(file.parent().unwrap()).unwrap();
+        fs::write(&file, r#"{"picks":[]}"#).unwrap();
+
+        let client = FplClient::new("http://127.0.0.1:9").with_offline(dir.clone());
+        assert!(client.fetch_manager_picks(1, 2).await.is_ok());
+        assert!(client.fetch_manager_picks(1, 3).await.is_err());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_gameweek_in_progress() {
+        let live = serde_json::json!({"events": [
+            {"id": 1, "is_current": false, "finished": true},
+            {"id": 2, "is_current": true, "finished": false},
+        ]});
+        let done = serde_json::json!({"events": [
+            {"id": 1, "is_current": true, "finished": true},
+        ]});
+        assert!(gameweek_in_progress(&live));
+        assert!(!gameweek_in_progress(&done));
+    }
+
+    #[test]
+    fn test_ttl() {
+        let dir = std::env::temp_dir().join(format!("fplr-test-ttl-{}", std::process::id()));
+        let client = FplClient::new("http://127.0.0.1:9").with_cache(Cache::new(dir.clone()));
+        let body = serde_json::json!({});
+        assert_eq!(client.ttl(Freshness::ElementSummary, &body), LIVE_TTL);
+
+        let deadline = (Utc::now() + chrono::TimeDelta::hours(3)).to_rfc3339();
+        let bootstrap = serde_json::json!({"events": [
+            {"id": 1, "is_current": true, "is_next": false, "finished": true},
+            {"id": 2, "is_current": false, "is_next": true, "finished": false,
+             "deadline_time": deadline},
+        ]});
+        let cache = Cache::new(dir.clone());
+        cache
+            .put(&client.url("bootstrap-static/"), &bootstrap, DEFAULT_TTL)
+            .unwrap();
+
+        // Between gameweeks a summary expires at the next deadline.
+        let ttl = client.ttl(Freshness::ElementSummary, &body);
+        assert!(ttl > Duration::from_secs(2 * 60 * 60) && ttl <= Duration::from_secs(3 * 60 * 60));
+        assert_eq!(client.ttl(Freshness::Event(1), &body), FINISHED_TTL);
+        assert_eq!(client.ttl(Freshness::Event(2), &body), LIVE_TTL);
+        assert_eq!(
+            client.ttl(Freshness::Fixtures, &serde_json::json!([])),
+            DEFAULT_TTL
         );
-        let response = reqwest::get(url).await?;
-        let json: ManagerPicks = response.json().await?;
-        Ok(json)
+        assert_eq!(client.ttl(Freshness::Bootstrap, &bootstrap), DEFAULT_TTL);
+
+        // Bootstrap data expires at the deadline when it is within the hour.
+        let soon = (Utc::now() + chrono::TimeDelta::minutes(20)).to_rfc3339();
+        let bootstrap = serde_json::json!({"events": [
+            {"id": 2, "is_current": false, "is_next": true, "finished": false,
+             "deadline_time": soon},
+        ]});
+        let ttl = client.ttl(Freshness::Bootstrap, &bootstrap);
+        assert!(ttl > Duration::from_secs(19 * 60) && ttl <= Duration::from_secs(20 * 60));
+        fs::remove_dir_all(dir).unwrap();
     }
 
-    pub async fn fetch_player_summary(
-        player_id: u64,
-    ) -> Result<PlayerSummary, Box<dyn std::error::Error>> {
-        let url = format!(
-            "https://fantasy.premierleague.com/api/element-summary/{}/",
-            player_id
+    #[tokio::test]
+    async fn test_fetch_uses_base_url() {
+        let (base_url, handle) = serve_once(r#"{"picks":[]}"#);
+        let client = FplClient::new(&base_url);
+        let picks = client.fetch_manager_picks(42, 7).await.unwrap();
+        assert!(picks.picks.is_empty());
+        assert_eq!(
+            handle.join().unwrap(),
+            vec!["GET /api/entry/42/event/7/picks/ HTTP/1.1"]
         );
-        let response = reqwest::get(url).await?;
-        let json: PlayerSummary = response.json().await?;
-        Ok(json)
+    }
+
+    #[tokio::test]
+    async fn test_save_snapshot_skips_missing_picks() {
+        let (base_url, handle) = serve(vec![
+            (
+                200,
+                "application/json",
+                r#"{"events":[{"id":1,"finished":true,"is_current":false}]}"#,
+            ),
+            (200, "application/json", "[]"),
+            (200, "application/json", r#"{"elements":[]}"#),
+            (200, "application/json", r#"{"team":[]}"#),
+            (404, "application/json", r#"{"detail":"Not found."}"#),
+        ]);
+        let dir = std::env::temp_dir().join(format!("fplr-test-snapshot-{}", std::process::id()));
+        let options = SnapshotOptions {
+            players: false,
+            managers: vec![42],
+        };
+        let written = FplClient::new(&base_url)
+            .save_snapshot(&dir, &options)
+            .await
+            .unwrap();
+        assert_eq!(written.len(), 4);
+        assert!(written.contains(&snapshot_file(&dir, "dream-team/1/")));
+        assert!(!snapshot_file(&dir, "entry/42/event/1/picks/").exists());
+        assert_eq!(handle.join().unwrap().len(), 5);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_fetch_retries_while_game_updates() {
+        let (base_url, handle) = serve(vec![
+            (503, "text/plain", "Service Unavailable"),
+            (200, "text/html", "<html>The game is being updated.</html>"),
+            (200, "application/json", r#"{"picks":[]}"#),
+        ]);
+        let client = FplClient::new(&base_url);
+        assert!(client.fetch_manager_picks(42, 7).await.is_ok());
+        assert_eq!(handle.join().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_gives_up_after_max_attempts() {
+        let responses = (0..MAX_ATTEMPTS)
+            .map(|_| (503, "text/plain", "Service Unavailable"))
+            .collect();
+        let (base_url, handle) = serve(responses);
+        let client = FplClient::new(&base_url);
+        assert!(matches!(
+            client.fetch_manager_picks(42, 7).await,
+            Err(FplError::GameUpdating)
+        ));
+        assert_eq!(handle.join().unwrap().len(), MAX_ATTEMPTS as usize);
     }
 }
@@ -0,0 +1,211 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Responses for data that may change at any moment (live gameweek).
+pub const LIVE_TTL: Duration = Duration::from_secs(2 * 60);
+/// Responses that change a few times a day at most.
+pub const DEFAULT_TTL: Duration = Duration::from_secs(60 * 60);
+/// Responses for finished gameweeks, which are effectively immutable.
+pub const FINISHED_TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+#[derive(Debug, Deserialize, Serialize)]
+struct CacheEntry {
+    url: String,
+    fetched_at: i64,
+    ttl_secs: u64,
+    body: Value,
+}
+
+#[derive(Debug)]
+pub struct CacheEntryInfo {
+    pub url: String,
+    pub size: u64,
+    pub age_secs: i64,
+    pub ttl_secs: u64,
+}
+
+impl CacheEntryInfo {
+    pub fn is_fresh(&self) -> bool {
+        self.age_secs < self.ttl_secs as i64
+    }
+}
+
+/// On-disk cache of API responses, one file per URL.
+pub struct Cache {
+    dir: PathBuf,
+    read: bool,
+}
+
+impl Cache {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir, read: true }
+    }
+
+    /// `$XDG_CACHE_HOME/fplr`, falling back to `~/.cache/fplr`.
+    pub fn default_dir() -> Option<PathBuf> {
+        std::env::var_os("XDG_CACHE_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".cache")))
+            .map(|dir| dir.join("fplr"))
+    }
+
+    /// Ignore existing entries but keep writing fresh responses.
+    pub fn refreshing(mut self) -> Self {
+        self.read = false;
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn path_for(&self, url: &str) -> PathBuf {
+        let key: String = url
+            .split_once("://")
+            .map_or(url, |(_, rest)| rest)
+            .trim_end_matches('/')
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' || c == '.' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        self.dir.join(format!("{}.json", key))
+    }
+
+    fn read_entry(&self, url: &str) -> Option<CacheEntry> {
+        let content = fs::read(self.path_for(url)).ok()?;
+        let entry: CacheEntry = serde_json::from_slice(&content).ok()?;
+        (entry.url == url).then_some(entry)
+    }
+
+    /// Returns the cached body for `url` if it has not expired.
+    pub fn get(&self, url: &str) -> Option<Value> {
+        if !self.read {
+            return None;
+        }
+        let entry = self.read_entry(url)?;
+        let age = Utc::now().timestamp() - entry.fetched_at;
+        (age < entry.ttl_secs as i64).then_some(entry.body)
+    }
+
+    /// Returns the cached body for `url` regardless of its age.
+    pub fn peek(&self, url: &str) -> Option<Value> {
+        self.read_entry(url).map(|entry| entry.body)
+    }
+
+    pub fn put(&self, url: &str, body: &Value, ttl: Duration) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let entry = CacheEntry {
+            url: url.to_string(),
+            fetched_at: Utc::now().timestamp(),
+            ttl_secs: ttl.as_secs(),
+            body: body.clone(),
+        };
+        fs::write(self.path_for(url), serde_json::to_vec(&entry)?)
+    }
+
+    pub fn entries(&self) -> io::Result<Vec<CacheEntryInfo>> {
+        let read_dir = match fs::read_dir(&self.dir) {
+            Ok(read_dir) => read_dir,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        let now = Utc::now().timestamp();
+        let mut entries: Vec<CacheEntryInfo> = read_dir
+            .filter_map(|dir_entry| {
+                let dir_entry = dir_entry.ok()?;
+                let size = dir_entry.metadata().ok()?.len();
+                let content = fs::read(dir_entry.path()).ok()?;
+                let entry: CacheEntry = serde_json::from_slice(&content).ok()?;
+                Some(CacheEntryInfo {
+                    url: entry.url,
+                    size,
+                    age_secs: now - entry.fetched_at,
+                    ttl_secs: entry.ttl_secs,
+                })
+            })
+            .collect();
+        entries.sort_by(|a, b| a.url.cmp(&b.url));
+        Ok(entries)
+    }
+
+    /// Removes every cached response and returns how many were deleted.
+    pub fn clear(&self) -> io::Result<usize> {
+        let read_dir = match fs::read_dir(&self.dir) {
+            Ok(read_dir) => read_dir,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e),
+        };
+        let mut removed = 0;
+        for dir_entry in read_dir {
+            let path = dir_entry?.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                fs::remove_file(path)?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn temp_cache(name: &str) -> Cache {
+        let dir = std::env::temp_dir().join(format!("fplr-test-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        Cache::new(dir)
+    }
+
+    #[test]
+    fn test_put_then_get() {
+        let cache = temp_cache("put-get");
+        let url = "https://example.com/api/event/1/live/";
+        cache
+            .put(url, &json!({"elements": []}), DEFAULT_TTL)
+            .unwrap();
+        assert_eq!(cache.get(url), Some(json!({"elements": []})));
+        assert_eq!(cache.entries().unwrap().len(), 1);
+        assert_eq!(cache.clear().unwrap(), 1);
+        assert_eq!(cache.get(url), None);
+    }
+
+    #[test]
+    fn test_expired_entry_is_only_peekable() {
+        let cache = temp_cache("expired");
+        let url = "https://example.com/api/fixtures/";
+        cache.put(url, &json!([]), Duration::ZERO).unwrap();
+        assert_eq!(cache.get(url), None);
+        assert_eq!(cache.peek(url), Some(json!([])));
+    }
+
+    #[test]
+    fn test_refreshing_skips_reads() {
+        let cache = temp_cache("refresh").refreshing();
+        let url = "https://example.com/api/bootstrap-static/";
+        cache.put(url, &json!({}), DEFAULT_TTL).unwrap();
+        assert_eq!(cache.get(url), None);
+    }
+
+    #[test]
+    fn test_path_for() {
+        let cache = Cache::new(PathBuf::from("/tmp/fplr"));
+        assert_eq!(
+            cache.path_for("https://fantasy.premierleague.com/api/event/12/live/"),
+            PathBuf::from("/tmp/fplr/fantasy.premierleague.com_api_event_12_live.json")
+        );
+    }
+}
@@ -0,0 +1,173 @@
+//! Minimal [RFC 5545](https://www.rfc-editor.org/rfc/rfc5545) iCalendar
+//! writer for exporting deadlines and fixtures.
+
+use chrono::{DateTime, TimeDelta, Utc};
+
+/// Longest content line in octets, excluding the CRLF.
+const MAX_LINE_OCTETS: usize = 75;
+
+/// A single `VEVENT`.
+#[derive(Debug, Clone)]
+pub struct CalendarEvent {
+    /// Globally unique and stable across exports, so re-importing a
+    /// calendar updates its events rather than duplicating them.
+    pub uid: String,
+    pub start: DateTime<Utc>,
+    pub duration: TimeDelta,
+    pub summary: String,
+    pub description: Option<String>,
+    /// Minutes before the start at which to show a reminder.
+    pub alarm_minutes: Option<u32>,
+}
+
+/// A `VCALENDAR` of events.
+#[derive(Debug, Clone)]
+pub struct Calendar {
+    pub name: String,
+    pub events: Vec<CalendarEvent>,
+}
+
+impl Calendar {
+    pub fn new(name: &str) -> Self {
+        Calendar {
+            name: name.to_string(),
+            events: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, event: CalendarEvent) {
+        self.events.push(event);
+    }
+
+    /// Serializes the calendar with CRLF line endings, stamping every event
+    /// with `stamp` as its `DTSTAMP`.
+    pub fn to_ics(&self, stamp: DateTime<Utc>) -> String {
+        let mut out = String::new();
+        let mut line = |content: String| {
+            fold_line(&mut out, &content);
+        };
+        line("BEGIN:VCALENDAR".to_string());
+        line("VERSION:2.0".to_string());
+        line(format!(
+            "PRODID:-//fplr//fplr {}//EN",
+            env!("CARGO_PKG_VERSION")
+        ));
+        line("CALSCALE:GREGORIAN".to_string());
+        line("METHOD:PUBLISH".to_string());
+        line(format!("X-WR-CALNAME:{}", escape_text(&self.name)));
+        for event in &self.events {
+            line("BEGIN:VEVENT".to_string());
+            line(format!("UID:{}", event.uid));
+            line(format!("DTSTAMP:{}", format_utc(stamp)));
+            line(format!("DTSTART:{}", format_utc(event.start)));
+            line(format!("DURATION:{}", format_duration(event.duration)));
+            line(format!("SUMMARY:{}", escape_text(&event.summary)));
+            if let Some(description) = &event.description {
+                line(format!("DESCRIPTION:{}", escape_text(description)));
+            }
+            if let Some(minutes) = event.alarm_minutes {
+                line("BEGIN:VALARM".to_string());
+                line("ACTION:DISPLAY".to_string());
+                line(format!("DESCRIPTION:{}", escape_text(&event.summary)));
+                line(format!("TRIGGER:-PT{}M", minutes));
+                line("END:VALARM".to_string());
+            }
+            line("END:VEVENT".to_string());
+        }
+        line("END:VCALENDAR".to_string());
+        out
+    }
+}
+
+fn format_utc(datetime: DateTime<Utc>) -> String {
+    datetime.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+fn format_duration(duration: TimeDelta) -> String {
+    let minutes = duration.num_minutes().max(0);
+    if minutes == 0 {
+        "PT0S".to_string()
+    } else if minutes % 60 == 0 {
+        format!("PT{}H", minutes / 60)
+    } else {
+        format!("PT{}M", minutes)
+    }
+}
+
+/// Escapes a TEXT value: backslashes, semicolons, commas and newlines.
+fn escape_text(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' | ';' | ',' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '\n' => out.push_str("\\n"),
+            '\r' => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Appends `content` to `out`, folding it into lines of at most 75 octets
+/// without splitting a UTF-8 character.
+fn fold_line(out: &mut String, content: &str) {
+    let mut width = 0;
+    for c in content.chars() {
+        if width + c.len_utf8() > MAX_LINE_OCTETS {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_to_ics() {
+        let mut calendar = Calendar::new("FPL");
+        calendar.push(CalendarEvent {
+            uid: "deadline-2025-1@fplr".to_string(),
+            start: at("2025-08-15T17:30:00Z"),
+            duration: TimeDelta::zero(),
+            summary: "FPL deadline: Gameweek 1".to_string(),
+            description: Some("Arsenal, Chelsea; Spurs".to_string()),
+            alarm_minutes: Some(90),
+        });
+        let ics = calendar.to_ics(at("2025-08-01T00:00:00Z"));
+
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(ics.ends_with("END:VEVENT\r\nEND:VCALENDAR\r\n"));
+        assert!(ics.contains("\r\nUID:deadline-2025-1@fplr\r\n"));
+        assert!(ics.contains("\r\nDTSTAMP:20250801T000000Z\r\n"));
+        assert!(ics.contains("\r\nDTSTART:20250815T173000Z\r\nDURATION:PT0S\r\n"));
+        assert!(ics.contains("\r\nDESCRIPTION:Arsenal\\, Chelsea\\; Spurs\r\n"));
+        assert!(ics.contains("\r\nTRIGGER:-PT90M\r\n"));
+        assert!(!ics.replace("\r\n", "").contains('\n'));
+    }
+
+    #[test]
+    fn test_fold_line() {
+        let mut out = String::new();
+        let content = format!("SUMMARY:{}", "ü".repeat(40));
+        fold_line(&mut out, &content);
+        let lines: Vec<&str> = out.trim_end_matches("\r\n").split("\r\n").collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines.iter().all(|line| line.len() <= MAX_LINE_OCTETS));
+        assert!(lines[1].starts_with(' '));
+        assert_eq!(lines.concat().replacen(" ", "", 1), content);
+        assert_eq!(format_duration(TimeDelta::minutes(105)), "PT105M");
+        assert_eq!(format_duration(TimeDelta::hours(2)), "PT2H");
+    }
+}
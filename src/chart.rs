@@ -0,0 +1,348 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use clap::ValueEnum;
+use rgb::RGB8;
+use textplots::{Chart, ColorPlot, LabelBuilder, LabelFormat, Plot, Shape};
+
+use fplr::models::{PlayerHistory, parse_decimal};
+
+use serde_json::{Value, json};
+
+use crate::render::{Table, terminal_width, use_styles};
+
+/// Colours of the plotted series, in order.
+const PALETTE: [RGB8; 4] = [
+    RGB8::new(0x4e, 0x9a, 0xe6),
+    RGB8::new(0xf2, 0x8e, 0x2b),
+    RGB8::new(0x59, 0xa1, 0x4f),
+    RGB8::new(0xe1, 0x57, 0x59),
+];
+
+const CHART_HEIGHT: u32 = 60;
+const MAX_CHART_WIDTH: u32 = 200;
+/// Columns taken by the y-axis labels to the right of the chart.
+const LABEL_COLUMNS: usize = 10;
+
+/// Per-gameweek metrics that `player-summary --graph` can chart.
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum GraphMetric {
+    Points,
+    /// Expected goals against goals scored
+    Xg,
+    Bps,
+    Minutes,
+    Price,
+    /// Number of managers who own the player
+    Ownership,
+}
+
+type Series = (&'static str, fn(&PlayerHistory) -> f64);
+
+/// How a series is drawn, so charts stay readable without colour.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum SeriesKind {
+    Line,
+    Dots,
+    /// Rolling averages
+    Steps,
+}
+
+impl SeriesKind {
+    fn label(&self) -> &str {
+        match self {
+            SeriesKind::Line => "line",
+            SeriesKind::Dots => "dots",
+            SeriesKind::Steps => "steps",
+        }
+    }
+
+    fn shape<'a>(&self, points: &'a [(f32, f32)]) -> Shape<'a> {
+        match self {
+            SeriesKind::Line => Shape::Lines(points),
+            SeriesKind::Dots => Shape::Points(points),
+            SeriesKind::Steps => Shape::Steps(points),
+        }
+    }
+}
+
+impl GraphMetric {
+    fn title(&self) -> &str {
+        match self {
+            GraphMetric::Points => "Points per gameweek",
+            GraphMetric::Xg => "xG vs goals",
+            GraphMetric::Bps => "BPS per gameweek",
+            GraphMetric::Minutes => "Minutes per gameweek",
+            GraphMetric::Price => "Price (£m)",
+            GraphMetric::Ownership => "Selected by (managers)",
+        }
+    }
+
+    fn series(&self) -> Vec<Series> {
+        match self {
+            GraphMetric::Points => vec![("Points", |h| h.total_points as f64)],
+            GraphMetric::Xg => vec![
+                ("xG", |h| parse_decimal(&h.expected_goals)),
+                ("Goals", |h| h.goals_scored as f64),
+            ],
+            GraphMetric::Bps => vec![("BPS", |h| h.bps as f64)],
+            GraphMetric::Minutes => vec![("Minutes", |h| h.minutes as f64)],
+            GraphMetric::Price => vec![("Price", |h| h.value as f64 / 10.0)],
+            GraphMetric::Ownership => vec![("Selected", |h| h.selected as f64)],
+        }
+    }
+
+    /// Whether values are snapshots rather than per-match counts, so a
+    /// double gameweek keeps its last value and the y axis need not start
+    /// at zero.
+    fn is_snapshot(&self) -> bool {
+        matches!(self, GraphMetric::Price | GraphMetric::Ownership)
+    }
+}
+
+/// One point per round, summing the matches of a double gameweek (or
+/// keeping the last one for snapshots).
+fn per_round(
+    histories: &[PlayerHistory],
+    value: fn(&PlayerHistory) -> f64,
+    snapshot: bool,
+) -> Vec<(f32, f32)> {
+    let mut rounds: BTreeMap<u64, f64> = BTreeMap::new();
+    for history in histories {
+        let entry = rounds.entry(history.round).or_insert(0.0);
+        if snapshot {
+            *entry = value(history);
+        } else {
+            *entry += value(history);
+        }
+    }
+    rounds
+        .into_iter()
+        .map(|(round, value)| (round as f32, value as f32))
+        .collect()
+}
+
+/// Trailing average over the last `window` points, starting once a full
+/// window is available.
+fn rolling_average(points: &[(f32, f32)], window: usize) -> Vec<(f32, f32)> {
+    if window == 0 {
+        return Vec::new();
+    }
+    points
+        .windows(window)
+        .map(|w| {
+            let (round, _) = w[w.len() - 1];
+            (round, w.iter().map(|(_, y)| y).sum::<f32>() / window as f32)
+        })
+        .collect()
+}
+
+/// The y range of a chart: from zero for counts, padded around the data
+/// for snapshots, and never empty.
+fn y_range(series: &[Vec<(f32, f32)>], snapshot: bool) -> (f32, f32) {
+    let values = series.iter().flatten().map(|(_, y)| *y);
+    let max = values.clone().fold(f32::NEG_INFINITY, f32::max);
+    let min = values.fold(f32::INFINITY, f32::min);
+    let (min, max) = if snapshot {
+        let padding = ((max - min) * 0.1).max(max.abs() * 0.01);
+        (min - padding, max + padding)
+    } else {
+        (min.min(0.0), max * 1.1)
+    };
+    if max > min {
+        (min, max)
+    } else {
+        (min, min + 1.0)
+    }
+}
+
+fn swatch(color: RGB8) -> String {
+    format!("\x1b[38;2;{};{};{}m━━\x1b[0m", color.r, color.g, color.b)
+}
+
+/// Prints a chart for each metric over the rounds the player has played,
+/// with an optional `rolling` average overlay per series.
+pub fn print_charts(histories: &[PlayerHistory], metrics: &[GraphMetric], rolling: Option<usize>) {
+    let Some(first) = histories.iter().map(|h| h.round).min() else {
+        return;
+    };
+    let last = histories.iter().map(|h| h.round).max().unwrap_or(first);
+    let (xmin, xmax) = (first as f32, last.max(first + 1) as f32);
+    let width = terminal_width()
+        .map(|columns| (columns.saturating_sub(LABEL_COLUMNS) * 2) as u32)
+        .unwrap_or(120)
+        .clamp(32, MAX_CHART_WIDTH);
+    let styled = use_styles();
+
+    for metric in metrics {
+        let mut labels = Vec::new();
+        let mut kinds = Vec::new();
+        let mut series = Vec::new();
+        for (i, (label, value)) in metric.series().into_iter().enumerate() {
+            let points = per_round(histories, value, metric.is_snapshot());
+            if let Some(window) = rolling.filter(|&w| w > 1) {
+                labels.push(format!("{} ({}-GW average)", label, window));
+                kinds.push(SeriesKind::Steps);
+                series.push(rolling_average(&points, window));
+            }
+            labels.push(label.to_string());
+            kinds.push(if i == 0 && points.len() > 1 {
+                SeriesKind::Line
+            } else {
+                SeriesKind::Dots
+            });
+            series.push(points);
+        }
+        let shapes: Vec<Shape> = kinds
+            .iter()
+            .zip(&series)
+            .map(|(kind, points)| kind.shape(points))
+            .collect();
+
+        let legend: Vec<String> = labels
+            .iter()
+            .zip(&kinds)
+            .enumerate()
+            .map(|(i, (label, kind))| {
+                if styled {
+                    format!("{} {}", swatch(PALETTE[i % PALETTE.len()]), label)
+                } else {
+                    format!("{} ({})", label, kind.label())
+                }
+            })
+            .collect();
+        println!("\n{}: {}", metric.title(), legend.join("  "));
+
+        let (ymin, ymax) = y_range(&series, metric.is_snapshot());
+        let mut chart = Chart::new_with_y_range(width, CHART_HEIGHT, xmin, xmax, ymin, ymax);
+        let mut chart = chart.x_label_format(LabelFormat::Custom(Box::new(|round| {
+            format!("GW{}", round.round())
+        })));
+        for (i, shape) in shapes.iter().enumerate() {
+            chart = if styled {
+                chart.linecolorplot(shape, PALETTE[i % PALETTE.len()])
+            } else {
+                chart.lineplot(shape)
+            };
+        }
+        chart.display();
+    }
+}
+
+/// The charted series as a table with a row per round, for output formats
+/// that cannot show a chart. Rolling averages are empty until a full
+/// window is available.
+pub fn series_table(
+    histories: &[PlayerHistory],
+    metrics: &[GraphMetric],
+    rolling: Option<usize>,
+) -> Table {
+    let mut columns: Vec<(String, String)> = vec![("round".to_string(), "GW".to_string())];
+    let mut series: Vec<BTreeMap<u64, f32>> = Vec::new();
+    for metric in metrics {
+        for (label, value) in metric.series() {
+            let key = label.to_lowercase();
+            if columns.iter().any(|(k, _)| *k == key) {
+                continue;
+            }
+            let points = per_round(histories, value, metric.is_snapshot());
+            let by_round = |points: &[(f32, f32)]| {
+                points
+                    .iter()
+                    .map(|&(round, value)| (round as u64, value))
+                    .collect()
+            };
+            series.push(by_round(&points));
+            if let Some(window) = rolling.filter(|&w| w > 1) {
+                columns.push((key.clone(), label.to_string()));
+                columns.push((
+                    format!("{}_average", key),
+                    format!("{} ({}-GW avg)", label, window),
+                ));
+                series.push(by_round(&rolling_average(&points, window)));
+            } else {
+                columns.push((key, label.to_string()));
+            }
+        }
+    }
+
+    let columns: Vec<(&str, &str)> = columns
+        .iter()
+        .map(|(key, title)| (key.as_str(), title.as_str()))
+        .collect();
+    let mut table = Table::new(&columns);
+    let rounds: BTreeSet<u64> = histories.iter().map(|h| h.round).collect();
+    for round in &rounds {
+        let mut row = vec![json!(round)];
+        row.extend(series.iter().map(|values| {
+            values.get(round).map_or(Value::Null, |&v| {
+                let v = (v as f64 * 100.0).round() / 100.0;
+                if v.fract() == 0.0 {
+                    json!(v as i64)
+                } else {
+                    json!(v)
+                }
+            })
+        }));
+        table.push(row);
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::OutputFormat;
+    use fplr::models::PlayerSummary;
+
+    fn histories() -> Vec<PlayerHistory> {
+        let summary: PlayerSummary =
+            serde_json::from_str(include_str!("../tests/data/element-summary.json")).unwrap();
+        summary.history
+    }
+
+    #[test]
+    fn test_per_round_uses_actual_rounds() {
+        let mut histories = histories();
+        // A second match in GW15 makes it a double gameweek.
+        histories[1].round = 15;
+        assert_eq!(
+            per_round(&histories, |h| h.total_points as f64, false),
+            vec![(13.0, 6.0), (15.0, 15.0)]
+        );
+        assert_eq!(
+            per_round(&histories, |h| h.value as f64 / 10.0, true),
+            vec![(13.0, 15.0), (15.0, 15.1)]
+        );
+    }
+
+    #[test]
+    fn test_rolling_average() {
+        let points = [(1.0, 2.0), (2.0, 4.0), (3.0, 9.0)];
+        assert_eq!(rolling_average(&points, 2), vec![(2.0, 3.0), (3.0, 6.5)]);
+        assert!(rolling_average(&points, 4).is_empty());
+    }
+
+    #[test]
+    fn test_series_table() {
+        let table = series_table(
+            &histories(),
+            &[GraphMetric::Points, GraphMetric::Price],
+            Some(2),
+        );
+        assert_eq!(
+            table.render(OutputFormat::Csv),
+            "GW,Points,Points (2-GW avg),Price,Price (2-GW avg)\n\
+             13,6,,15,\n\
+             14,13,9.5,15,15\n\
+             15,2,7.5,15.1,15.05\n"
+        );
+    }
+
+    #[test]
+    fn test_y_range() {
+        let series = vec![vec![(1.0, 14.8), (2.0, 15.2)]];
+        let (min, max) = y_range(&series, true);
+        assert!(min < 14.8 && min > 14.0 && max > 15.2 && max < 16.0);
+        assert_eq!(y_range(&[vec![(1.0, 0.0)]], false), (0.0, 1.0));
+    }
+}
@@ -1,451 +1,1337 @@
+use std::cmp::Reverse;
 use std::collections::HashMap;
+use std::fs;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
 
-use chrono::{DateTime, Utc};
-use textplots::{Chart, Plot, Shape};
+use chrono::{DateTime, Datelike, TimeDelta, Utc};
+use clap::ValueEnum;
+use serde_json::{Value, json};
 
-use crate::api::FplClient;
-use crate::models::{Element, Position, SortBy, StatsPoints, Team};
+use fplr::api::{FplClient, SnapshotOptions};
+use fplr::cache::Cache;
+use fplr::calendar::{Calendar, CalendarEvent};
+use fplr::error::FplError;
+use fplr::filter::Filter;
+use fplr::helpers::{
+    TickerRow, blank_and_double_gameweeks, create_player_map, create_team_map,
+    create_team_short_name_map, event_range, format_opponent, position_name, resolve_event,
+    resolve_team_ids, sort_by_keys, team_fixtures, ticker_rows,
+};
+use fplr::models::{
+    Element, EventSelector, Fixture, HistoryStat, PlayerFixture, PlayerSeason, PlayerStat,
+    PlayerStatus, PlayerSummary, Position, SortBy, StatsPoints, Team,
+};
+use fplr::search::{resolve_player, search_players};
 
-fn format_datetime(datetime_str: &str) -> String {
-    let dt = datetime_str.parse::<DateTime<Utc>>().unwrap();
-    dt.format("%Y-%m-%d %H:%M UTC").to_string()
+use crate::chart::{GraphMetric, print_charts, series_table};
+use crate::render::{CellStyle, OutputFormat, Table, TimeFormat, number_or_string, print_sections};
+
+/// Shared state for every command.
+pub struct Context {
+    pub client: FplClient,
+    pub output: OutputFormat,
+    pub time: TimeFormat,
+}
+
+/// Kickoff text of a fixture: "Postponed" once it has been taken out of
+/// its gameweek, and "TBD" while its kickoff time is not yet set.
+fn format_kickoff(time: &TimeFormat, event: Option<u64>, kickoff_time: Option<&str>) -> String {
+    match (event, kickoff_time) {
+        (None, _) => "Postponed".to_string(),
+        (Some(_), None) => "TBD".to_string(),
+        (Some(_), Some(kickoff_time)) => time.format(kickoff_time),
+    }
 }
 
-fn create_team_map(teams: &[Team]) -> HashMap<u64, String> {
-    teams
+fn format_duration(secs: i64) -> String {
+    let secs = secs.max(0);
+    if secs < 60 {
+        format!("{}s", secs)
+    } else if secs < 60 * 60 {
+        format!("{}m", secs / 60)
+    } else if secs < 24 * 60 * 60 {
+        format!("{}h", secs / (60 * 60))
+    } else {
+        format!("{}d", secs / (24 * 60 * 60))
+    }
+}
+
+fn involves_any(fixture: &Fixture, team_ids: &[u64]) -> bool {
+    team_ids.is_empty() || team_ids.contains(&fixture.team_h) || team_ids.contains(&fixture.team_a)
+}
+
+/// Postponed fixtures without a gameweek involving any of `team_ids` (all
+/// teams when empty), ordered by ID.
+fn unscheduled_fixtures<'a>(fixtures: &'a [Fixture], team_ids: &[u64]) -> Vec<&'a Fixture> {
+    fixtures
         .iter()
-        .map(|team| (team.id, team.name.clone()))
+        .filter(|f| f.event.is_none() && !f.finished && involves_any(f, team_ids))
         .collect()
 }
 
-fn find_team_ids_by_name(teams: &[Team], name: &str) -> Vec<u64> {
-    let search_term = name.to_lowercase();
-    teams
+/// Fixtures of gameweeks `events` involving any of `team_ids` (all teams
+/// when empty), ordered by kickoff. `finished` keeps only results or only
+/// unplayed fixtures.
+fn select_fixtures<'a>(
+    fixtures: &'a [Fixture],
+    events: RangeInclusive<u64>,
+    team_ids: &[u64],
+    finished: Option<bool>,
+) -> Vec<&'a Fixture> {
+    let mut selected: Vec<&Fixture> = fixtures
         .iter()
-        .filter(|team| {
-            team.name.to_lowercase().contains(&search_term)
-                || team.short_name.to_lowercase().contains(&search_term)
-        })
-        .map(|team| team.id)
-        .collect()
+        .filter(|f| f.event.is_some_and(|event| events.contains(&event)))
+        .filter(|f| involves_any(f, team_ids))
+        .filter(|f| finished.is_none_or(|finished| finished == f.finished))
+        .collect();
+    // Fixtures without a kickoff time yet go last in their gameweek.
+    selected.sort_by(|a, b| {
+        (a.event, a.kickoff_time.is_none(), &a.kickoff_time, a.id).cmp(&(
+            b.event,
+            b.kickoff_time.is_none(),
+            &b.kickoff_time,
+            b.id,
+        ))
+    });
+    selected
 }
 
-fn create_player_map(elements: &[Element]) -> HashMap<u64, String> {
-    elements
+/// Options of the `calendar` command.
+pub struct CalendarOptions {
+    /// File to write; stdout when `None`.
+    pub out: Option<PathBuf>,
+    /// Minutes before each deadline to show a reminder; none when zero.
+    pub alarm: u32,
+    /// Team names whose fixtures are included alongside the deadlines.
+    pub teams: Vec<String>,
+}
+
+/// Rough length of a match including half-time and stoppages.
+const FIXTURE_DURATION_MINUTES: i64 = 115;
+
+pub async fn handle_calendar(ctx: &Context, options: CalendarOptions) -> Result<(), FplError> {
+    let bootstrap_data = ctx.client.fetch_bootstrap_static().await?;
+    let events = &bootstrap_data.events;
+    // Gameweek IDs restart every season, so deadline UIDs include the year
+    // the season started in.
+    let season = events
         .iter()
-        .map(|player| (player.id, player.web_name.clone()))
-        .collect()
+        .min_by_key(|e| e.id)
+        .and_then(|e| e.deadline_time.parse::<DateTime<Utc>>().ok())
+        .map(|deadline| deadline.year())
+        .unwrap_or_default();
+
+    let mut calendar = Calendar::new("Fantasy Premier League");
+    for event in events {
+        let Ok(deadline) = event.deadline_time.parse::<DateTime<Utc>>() else {
+            continue;
+        };
+        calendar.push(CalendarEvent {
+            uid: format!("deadline-{}-{}@fplr", season, event.id),
+            start: deadline,
+            duration: TimeDelta::zero(),
+            summary: format!("FPL deadline: {}", event.name),
+            description: None,
+            alarm_minutes: (options.alarm > 0).then_some(options.alarm),
+        });
+    }
+
+    let mut team_ids = Vec::new();
+    for name in &options.teams {
+        team_ids.extend(resolve_team_ids(&bootstrap_data.teams, name)?);
+    }
+    if !team_ids.is_empty() {
+        let team_map = create_team_map(&bootstrap_data.teams);
+        let team_name = |id: u64| team_map.get(&id).map(|s| s.as_str()).unwrap_or("Unknown");
+        let fixtures = ctx.client.fetch_fixtures().await?;
+        // Postponed fixtures are left out until they have a date; their UID
+        // is the fixture code, so they are added on a later export.
+        for fixture in select_fixtures(&fixtures, 1..=u64::MAX, &team_ids, None) {
+            let (Some(event), Some(Ok(kickoff))) = (
+                fixture.event,
+                fixture
+                    .kickoff_time
+                    .as_deref()
+                    .map(|k| k.parse::<DateTime<Utc>>()),
+            ) else {
+                continue;
+            };
+            let summary = match (fixture.team_h_score, fixture.team_a_score) {
+                (Some(home), Some(away)) if fixture.finished => format!(
+                    "{} {}-{} {}",
+                    team_name(fixture.team_h),
+                    home,
+                    away,
+                    team_name(fixture.team_a)
+                ),
+                _ => format!(
+                    "{} v {}",
+                    team_name(fixture.team_h),
+                    team_name(fixture.team_a)
+                ),
+            };
+            calendar.push(CalendarEvent {
+                uid: format!("fixture-{}@fplr", fixture.code),
+                start: kickoff,
+                duration: TimeDelta::minutes(FIXTURE_DURATION_MINUTES),
+                summary,
+                description: Some(format!("Gameweek {}", event)),
+                alarm_minutes: None,
+            });
+        }
+    }
+
+    let ics = calendar.to_ics(Utc::now());
+    match &options.out {
+        Some(path) => {
+            fs::write(path, ics)?;
+            println!(
+                "Saved {} events to {}",
+                calendar.events.len(),
+                path.display()
+            );
+        }
+        None => print!("{}", ics),
+    }
+    Ok(())
 }
 
-pub async fn handle_dream_team(event_id: u32) {
-    match FplClient::fetch_bootstrap_static().await {
-        Ok(bootstrap_data) => {
-            let player_map = create_player_map(&bootstrap_data.elements);
+/// Which end of a `compare` row is highlighted as the best value.
+#[derive(Clone, Copy, PartialEq)]
+enum Better {
+    Higher,
+    Lower,
+    Neither,
+}
 
-            match FplClient::fetch_dream_team(event_id).await {
-                Ok(data) => {
-                    let mut team = data.team;
-                    team.sort_by(|a, b| b.points.cmp(&a.points));
+/// Number of upcoming gameweeks shown by `compare`.
+const COMPARE_FIXTURE_WEEKS: u64 = 5;
 
-                    println!("{:<4} {:<20} {:<12}", "ID", "Name", "Points");
-                    for t in team.iter() {
-                        let name = player_map
-                            .get(&t.element)
-                            .map(|s| s.as_str())
-                            .unwrap_or("Unknown");
+pub async fn handle_compare(ctx: &Context, players: &[String], last: u64) -> Result<(), FplError> {
+    let data = ctx.client.fetch_bootstrap_static().await?;
+    let fixtures = ctx.client.fetch_fixtures().await?;
+    let team_map = create_team_map(&data.teams);
+    let short_names = create_team_short_name_map(&data.teams);
+    let current_event = data
+        .events
+        .iter()
+        .find(|e| e.is_current)
+        .map_or(0, |e| e.id);
+    let last_event = data.events.iter().map(|e| e.id).max().unwrap_or(0);
+    let upcoming: Vec<u64> = match data.events.iter().find(|e| e.is_next) {
+        Some(next) => (next.id..(next.id + COMPARE_FIXTURE_WEEKS).min(last_event + 1)).collect(),
+        None => Vec::new(),
+    };
 
-                        println!("{:<4} {:<20} {:<12}", t.element, name, t.points);
-                    }
-                }
-                Err(e) => {
-                    eprintln!("Error: {}", e);
+    let mut selected: Vec<(&Element, PlayerSummary)> = Vec::new();
+    for player in players {
+        let element = resolve_player(&data.elements, player)?;
+        let summary = ctx.client.fetch_player_summary(element.id).await?;
+        selected.push((element, summary));
+    }
+
+    let row = |title: String, better: Better, value: &dyn Fn(&Element, &PlayerSummary) -> Value| {
+        let values: Vec<Value> = selected.iter().map(|(p, s)| value(p, s)).collect();
+        (title, better, values)
+    };
+
+    let mut rows = vec![
+        row("Team".to_string(), Better::Neither, &|p, _| {
+            json!(team_map.get(&p.team).map_or("Unknown", |s| s.as_str()))
+        }),
+        row("Pos".to_string(), Better::Neither, &|p, _| {
+            json!(position_name(p.element_type))
+        }),
+        row("Cost".to_string(), Better::Lower, &|p, _| {
+            json!(p.now_cost as f64 / 10.0)
+        }),
+        row("Selected".to_string(), Better::Neither, &|p, _| {
+            number_or_string(&p.selected_by_percent)
+        }),
+        row("Form".to_string(), Better::Higher, &|p, _| {
+            number_or_string(&p.form)
+        }),
+        row("Points".to_string(), Better::Higher, &|p, _| {
+            json!(p.total_points)
+        }),
+        row("Pts/Game".to_string(), Better::Higher, &|p, _| {
+            number_or_string(&p.points_per_game)
+        }),
+        row("Minutes".to_string(), Better::Higher, &|p, _| {
+            json!(p.minutes)
+        }),
+        row("Goals".to_string(), Better::Higher, &|p, _| {
+            json!(p.goals_scored)
+        }),
+        row("Assists".to_string(), Better::Higher, &|p, _| {
+            json!(p.assists)
+        }),
+        row("Clean sheets".to_string(), Better::Higher, &|p, _| {
+            json!(p.clean_sheets)
+        }),
+        row("Bonus".to_string(), Better::Higher, &|p, _| json!(p.bonus)),
+        row("xG".to_string(), Better::Higher, &|p, _| {
+            number_or_string(&p.expected_goals)
+        }),
+        row("xA".to_string(), Better::Higher, &|p, _| {
+            number_or_string(&p.expected_assists)
+        }),
+        row("xG/90".to_string(), Better::Higher, &|p, _| {
+            json!(p.expected_goals_per_90)
+        }),
+        row("xA/90".to_string(), Better::Higher, &|p, _| {
+            json!(p.expected_assists_per_90)
+        }),
+        row(format!("Pts last {} GW", last), Better::Higher, &|_, s| {
+            let from = current_event.saturating_sub(last);
+            let points: i64 = s
+                .history
+                .iter()
+                .filter(|h| h.round > from && h.round <= current_event)
+                .map(|h| h.total_points)
+                .sum();
+            json!(points)
+        }),
+    ];
+    for &event in &upcoming {
+        rows.push(row(format!("GW{}", event), Better::Neither, &|p, _| {
+            let cells: Vec<String> = team_fixtures(&fixtures, p.team, event)
+                .iter()
+                .map(|(opponent, home, difficulty)| {
+                    format!(
+                        "{} {}",
+                        format_opponent(&short_names, *opponent, *home),
+                        difficulty
+                    )
+                })
+                .collect();
+            json!(if cells.is_empty() {
+                "-".to_string()
+            } else {
+                cells.join(", ")
+            })
+        }));
+    }
+    if !upcoming.is_empty() {
+        rows.push(row("Avg FDR".to_string(), Better::Lower, &|p, _| {
+            let difficulties: Vec<f64> = upcoming
+                .iter()
+                .flat_map(|&event| team_fixtures(&fixtures, p.team, event))
+                .map(|(_, _, difficulty)| difficulty as f64)
+                .collect();
+            if difficulties.is_empty() {
+                Value::Null
+            } else {
+                let average = difficulties.iter().sum::<f64>() / difficulties.len() as f64;
+                json!((average * 10.0).round() / 10.0)
+            }
+        }));
+    }
+
+    let keys: Vec<String> = selected.iter().map(|(p, _)| p.id.to_string()).collect();
+    let mut columns = vec![("stat", "Stat")];
+    columns.extend(
+        keys.iter()
+            .map(String::as_str)
+            .zip(selected.iter().map(|(p, _)| p.web_name.as_str())),
+    );
+    let mut table = Table::new(&columns).max_width("stat", 16);
+    for (r, (title, better, values)) in rows.into_iter().enumerate() {
+        let numbers: Vec<Option<f64>> = values.iter().map(Value::as_f64).collect();
+        let best = match better {
+            Better::Higher => numbers.iter().flatten().copied().reduce(f64::max),
+            Better::Lower => numbers.iter().flatten().copied().reduce(f64::min),
+            Better::Neither => None,
+        };
+        let distinct = numbers.iter().flatten().any(|&n| Some(n) != best);
+        if let Some(best) = best.filter(|_| distinct) {
+            for (i, number) in numbers.iter().enumerate() {
+                if *number == Some(best) {
+                    table.highlight(r, i + 1);
                 }
             }
         }
-        Err(e) => {
-            eprintln!("Error: {}", e);
-        }
+        let mut cells = vec![json!(title)];
+        cells.extend(values);
+        table.push(cells);
     }
+    table.print(ctx.output);
+    Ok(())
 }
 
-pub async fn handle_gameweek() {
-    match FplClient::fetch_bootstrap_static().await {
-        Ok(data) => {
-            println!(
-                "{:<4} {:<16} {:<12} {:<20}",
-                "ID", "Name", "Status", "Deadline"
+pub struct DgwOptions {
+    pub from: Option<u64>,
+    pub to: Option<u64>,
+    /// Show which players of this manager's latest squad are affected.
+    pub manager: Option<u64>,
+}
+
+pub async fn handle_dgw(ctx: &Context, options: DgwOptions) -> Result<(), FplError> {
+    let data = ctx.client.fetch_bootstrap_static().await?;
+    let last_event = data.events.iter().map(|e| e.id).max().unwrap_or(0);
+    let from = match (options.from, data.events.iter().find(|e| e.is_next)) {
+        (Some(from), _) => from,
+        (None, Some(next)) => next.id,
+        (None, None) => {
+            eprintln!(
+                "There is no upcoming gameweek; the season is over. Use --from to look back."
             );
-            for event in data.events {
-                let status = if event.is_current {
-                    "Current"
-                } else if event.is_next {
-                    "Next"
-                } else if event.finished {
-                    "Finished"
-                } else {
-                    "Upcoming"
-                };
-                println!(
-                    "{:<4} {:<16} {:<12} {:<20}",
-                    event.id,
-                    event.name,
-                    status,
-                    format_datetime(&event.deadline_time)
-                );
-            }
+            return Ok(());
         }
-        Err(e) => {
-            eprintln!("Error: {}", e);
+    };
+    let events = event_range(&data.events, from, options.to.unwrap_or(last_event))?;
+    let fixtures = ctx.client.fetch_fixtures().await?;
+    let team_map = create_team_map(&data.teams);
+    let short_names = create_team_short_name_map(&data.teams);
+
+    let squad: Vec<&Element> = match options.manager {
+        Some(manager_id) => {
+            let Some(event) = data.events.iter().find(|e| e.is_current) else {
+                return Err(FplError::SeasonNotStarted);
+            };
+            let picks = ctx
+                .client
+                .fetch_manager_picks(manager_id, event.id as u32)
+                .await?;
+            picks
+                .picks
+                .iter()
+                .filter_map(|pick| data.elements.iter().find(|e| e.id == pick.element))
+                .collect()
+        }
+        None => Vec::new(),
+    };
+    let squad_players = |team: u64| -> Value {
+        if options.manager.is_none() {
+            return Value::Null;
+        }
+        let names: Vec<&str> = squad
+            .iter()
+            .filter(|player| player.team == team)
+            .map(|player| player.web_name.as_str())
+            .collect();
+        json!(names.join(", "))
+    };
+    let opponents = |fixtures: &[(u64, bool, u8)]| -> String {
+        fixtures
+            .iter()
+            .map(|(opponent, home, _)| format_opponent(&short_names, *opponent, *home))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    let mut columns = vec![
+        ("event", "GW"),
+        ("team", "Team"),
+        ("kind", "Type"),
+        ("fixtures", "Fixtures"),
+    ];
+    if options.manager.is_some() {
+        columns.push(("players", "Players"));
+    }
+    let mut table = Table::new(&columns);
+    let mut push = |event: Value, team: u64, kind: &str, fixtures: String| {
+        let mut row = vec![
+            event,
+            json!(team_map.get(&team).map(|s| s.as_str()).unwrap_or("Unknown")),
+            json!(kind),
+            json!(fixtures),
+        ];
+        if options.manager.is_some() {
+            row.push(squad_players(team));
         }
+        table.push(row);
+    };
+
+    let team_ids: Vec<u64> = data.teams.iter().map(|team| team.id).collect();
+    for (event, team, count) in blank_and_double_gameweeks(&fixtures, &team_ids, events) {
+        let kind = match count {
+            0 => "Blank",
+            2 => "Double",
+            _ => "Triple",
+        };
+        push(
+            json!(event),
+            team,
+            kind,
+            opponents(&team_fixtures(&fixtures, team, event)),
+        );
+    }
+    // Fixtures without a gameweek are postponed or not yet scheduled; they
+    // usually become a double once rearranged.
+    for fixture in fixtures.iter().filter(|f| f.event.is_none() && !f.finished) {
+        let home = (fixture.team_a, true, fixture.team_h_difficulty);
+        let away = (fixture.team_h, false, fixture.team_a_difficulty);
+        push(
+            Value::Null,
+            fixture.team_h,
+            "Unscheduled",
+            opponents(&[home]),
+        );
+        push(
+            Value::Null,
+            fixture.team_a,
+            "Unscheduled",
+            opponents(&[away]),
+        );
     }
+    table.print(ctx.output);
+    Ok(())
 }
 
-pub async fn handle_live(event: u32, limit: usize) {
-    match FplClient::fetch_bootstrap_static().await {
-        Ok(bootstrap_data) => {
-            let player_map = create_player_map(&bootstrap_data.elements);
-
-            match FplClient::fetch_live(event).await {
-                Ok(data) => {
-                    let mut elements = data.elements;
-                    elements.sort_by(|a, b| b.stats.total_points.cmp(&a.stats.total_points));
-
-                    println!(
-                        "{:<4} {:<20} {:<8} {:<4} {:<4} {:<4} {:<4} {:<4} {:<4} {:<4} {:<4} {:<4} {:<4} {:<4} {:<4}",
-                        "ID",
-                        "Name",
-                        "Total",
-                        "Min",
-                        "G",
-                        "A",
-                        "CS",
-                        "GC",
-                        "S",
-                        "PS",
-                        "PM",
-                        "YC",
-                        "RC",
-                        "OG",
-                        "B"
-                    );
-                    for element in elements.iter().take(limit) {
-                        let name = player_map
-                            .get(&element.id)
-                            .map(|s| s.as_str())
-                            .unwrap_or("Unknown");
-
-                        let mut stats = StatsPoints::default();
-                        for explain in &element.explain {
-                            for stat in &explain.stats {
-                                match stat.identifier.as_str() {
-                                    "minutes" => stats.minutes += stat.points,
-                                    "goals_scored" => stats.goals_scored += stat.points,
-                                    "assists" => stats.assists += stat.points,
-                                    "clean_sheets" => stats.clean_sheets += stat.points,
-                                    "goals_conceded" => stats.goals_conceded += stat.points,
-                                    "saves" => stats.saves += stat.points,
-                                    "penalties_saved" => stats.penalties_saved += stat.points,
-                                    "penalties_missed" => stats.penalties_missed += stat.points,
-                                    "yellow_cards" => stats.yellow_cards += stat.points,
-                                    "red_cards" => stats.red_cards += stat.points,
-                                    "own_goals" => stats.own_goals += stat.points,
-                                    "bonus" => stats.bonus += stat.points,
-                                    _ => {}
-                                }
-                            }
-                        }
-
-                        println!(
-                            "{:<4} {:<20} {:<8} {:<4} {:<4} {:<4} {:<4} {:<4} {:<4} {:<4} {:<4} {:<4} {:<4} {:<4} {:<4}",
-                            element.id,
-                            name,
-                            element.stats.total_points,
-                            stats.minutes,
-                            stats.goals_scored,
-                            stats.assists,
-                            stats.clean_sheets,
-                            stats.goals_conceded,
-                            stats.saves,
-                            stats.penalties_saved,
-                            stats.penalties_missed,
-                            stats.yellow_cards,
-                            stats.red_cards,
-                            stats.own_goals,
-                            stats.bonus
-                        );
-                    }
-                }
-                Err(e) => {
-                    eprintln!("Error: {}", e);
+pub async fn handle_dream_team(ctx: &Context, event: EventSelector) -> Result<(), FplError> {
+    let bootstrap_data = ctx.client.fetch_bootstrap_static().await?;
+    let player_map = create_player_map(&bootstrap_data.elements);
+    let event_id = resolve_event(&bootstrap_data.events, event)? as u32;
+
+    let data = ctx.client.fetch_dream_team(event_id).await?;
+    let mut team = data.team;
+    team.sort_by_key(|t| Reverse(t.points));
+
+    let mut table =
+        Table::new(&[("id", "ID"), ("name", "Name"), ("points", "Points")]).max_width("name", 20);
+    for t in team.iter() {
+        let name = player_map
+            .get(&t.element)
+            .map(|s| s.as_str())
+            .unwrap_or("Unknown");
+
+        table.push(vec![json!(t.element), json!(name), json!(t.points)]);
+    }
+    table.print(ctx.output);
+    Ok(())
+}
+
+pub async fn handle_gameweek(ctx: &Context) -> Result<(), FplError> {
+    let data = ctx.client.fetch_bootstrap_static().await?;
+    let mut table = Table::new(&[
+        ("id", "ID"),
+        ("name", "Name"),
+        ("status", "Status"),
+        ("deadline", "Deadline"),
+    ]);
+    for event in data.events {
+        let status = if event.is_current {
+            "Current"
+        } else if event.is_next {
+            "Next"
+        } else if event.finished {
+            "Finished"
+        } else {
+            "Upcoming"
+        };
+        table.push(vec![
+            json!(event.id),
+            json!(event.name),
+            json!(status),
+            json!(ctx.time.format(&event.deadline_time)),
+        ]);
+    }
+    table.print(ctx.output);
+    Ok(())
+}
+
+pub async fn handle_live(
+    ctx: &Context,
+    event: EventSelector,
+    limit: usize,
+    sort: Vec<SortBy>,
+    ascending: bool,
+) -> Result<(), FplError> {
+    let bootstrap_data = ctx.client.fetch_bootstrap_static().await?;
+    let event = resolve_event(&bootstrap_data.events, event)? as u32;
+    let player_map = create_player_map(&bootstrap_data.elements);
+    let element_map: HashMap<u64, &Element> = bootstrap_data
+        .elements
+        .iter()
+        .map(|element| (element.id, element))
+        .collect();
+
+    let data = ctx.client.fetch_live(event).await?;
+    let mut elements = data.elements;
+    sort_by_keys(&mut elements, &sort, ascending, |element, key| {
+        key.live_value(&element.stats, element_map.get(&element.id).copied())
+    });
+
+    let mut table = Table::new(&[
+        ("id", "ID"),
+        ("name", "Name"),
+        ("total_points", "Total"),
+        ("minutes", "Min"),
+        ("goals_scored", "G"),
+        ("assists", "A"),
+        ("clean_sheets", "CS"),
+        ("goals_conceded", "GC"),
+        ("saves", "S"),
+        ("penalties_saved", "PS"),
+        ("penalties_missed", "PM"),
+        ("yellow_cards", "YC"),
+        ("red_cards", "RC"),
+        ("own_goals", "OG"),
+        ("bonus", "B"),
+    ])
+    .max_width("name", 20);
+    for element in elements.iter().take(limit) {
+        let name = player_map
+            .get(&element.id)
+            .map(|s| s.as_str())
+            .unwrap_or("Unknown");
+
+        let mut stats = StatsPoints::default();
+        for explain in &element.explain {
+            for stat in &explain.stats {
+                match stat.identifier.as_str() {
+                    "minutes" => stats.minutes += stat.points,
+                    "goals_scored" => stats.goals_scored += stat.points,
+                    "assists" => stats.assists += stat.points,
+                    "clean_sheets" => stats.clean_sheets += stat.points,
+                    "goals_conceded" => stats.goals_conceded += stat.points,
+                    "saves" => stats.saves += stat.points,
+                    "penalties_saved" => stats.penalties_saved += stat.points,
+                    "penalties_missed" => stats.penalties_missed += stat.points,
+                    "yellow_cards" => stats.yellow_cards += stat.points,
+                    "red_cards" => stats.red_cards += stat.points,
+                    "own_goals" => stats.own_goals += stat.points,
+                    "bonus" => stats.bonus += stat.points,
+                    _ => {}
                 }
             }
         }
-        Err(e) => {
-            eprintln!("Error: {}", e);
-        }
+
+        table.push(vec![
+            json!(element.id),
+            json!(name),
+            json!(element.stats.total_points),
+            json!(stats.minutes),
+            json!(stats.goals_scored),
+            json!(stats.assists),
+            json!(stats.clean_sheets),
+            json!(stats.goals_conceded),
+            json!(stats.saves),
+            json!(stats.penalties_saved),
+            json!(stats.penalties_missed),
+            json!(stats.yellow_cards),
+            json!(stats.red_cards),
+            json!(stats.own_goals),
+            json!(stats.bonus),
+        ]);
     }
+    table.print(ctx.output);
+    Ok(())
 }
 
-pub async fn handle_player(
-    sort: SortBy,
-    position: Option<Position>,
-    limit: usize,
-    team: Option<String>,
-) {
-    match FplClient::fetch_bootstrap_static().await {
-        Ok(data) => {
-            let team_map = create_team_map(&data.teams);
-            let target_team_ids = if let Some(ref team_name) = team {
-                find_team_ids_by_name(&data.teams, team_name)
+/// Filters, ordering and extra columns of the `player` command.
+pub struct PlayerOptions {
+    pub sort: Vec<SortBy>,
+    pub ascending: bool,
+    pub position: Option<Position>,
+    pub limit: usize,
+    pub team: Option<String>,
+    pub status: Vec<PlayerStatus>,
+    pub columns: Vec<PlayerStat>,
+    pub filter: Option<Filter>,
+}
+
+fn stat_cell(stat: PlayerStat, element: &Element) -> Value {
+    match stat.value(element) {
+        Some(value) if stat.is_integer() => json!(value as i64),
+        Some(value) => json!(value),
+        None => Value::Null,
+    }
+}
+
+fn history_cell(stat: HistoryStat, value: f64) -> Value {
+    if stat.is_integer() {
+        json!(value as i64)
+    } else {
+        json!(value)
+    }
+}
+
+pub async fn handle_player(ctx: &Context, options: PlayerOptions) -> Result<(), FplError> {
+    let data = ctx.client.fetch_bootstrap_static().await?;
+    let team_map = create_team_map(&data.teams);
+    let target_team_ids = if let Some(ref team_name) = options.team {
+        resolve_team_ids(&data.teams, team_name)?
+    } else {
+        Vec::new()
+    };
+
+    let mut players: Vec<Element> = data
+        .elements
+        .into_iter()
+        .filter(|player| {
+            let position_match = if let Some(ref pos) = options.position {
+                player.element_type == pos.element_type_id() as u64
+            } else {
+                true
+            };
+            let team_match = if options.team.is_some() {
+                target_team_ids.contains(&player.team)
             } else {
-                Vec::new()
+                true
             };
+            let status_match = options.status.is_empty()
+                || options.status.iter().any(|s| s.code() == player.status);
+            let filter_match = options.filter.as_ref().is_none_or(|f| f.matches(player));
+            position_match && team_match && status_match && filter_match
+        })
+        .collect();
 
-            let mut players: Vec<Element> = data
-                .elements
-                .into_iter()
-                .filter(|player| {
-                    let position_match = if let Some(ref pos) = position {
-                        player.element_type == pos.element_type_id() as u64
-                    } else {
-                        true
-                    };
-                    let team_match = if team.is_some() {
-                        target_team_ids.contains(&player.team)
-                    } else {
-                        true
-                    };
-                    position_match && team_match
-                })
-                .collect();
+    sort_by_keys(
+        &mut players,
+        &options.sort,
+        options.ascending,
+        |player, key| key.value(player),
+    );
 
-            match sort {
-                SortBy::Cost => players.sort_by(|a, b| b.now_cost.cmp(&a.now_cost)),
-                SortBy::Form => players.sort_by(|a, b| {
-                    let form_a = a.form.parse::<f64>().unwrap_or(0.0);
-                    let form_b = b.form.parse::<f64>().unwrap_or(0.0);
-                    form_b.partial_cmp(&form_a).unwrap()
-                }),
-                SortBy::Points => players.sort_by(|a, b| b.total_points.cmp(&a.total_points)),
-                SortBy::SelectedBy => players.sort_by(|a, b| {
-                    let selected_by_a = a.selected_by_percent.parse::<f64>().unwrap_or(0.0);
-                    let selected_by_b = b.selected_by_percent.parse::<f64>().unwrap_or(0.0);
-                    selected_by_b.partial_cmp(&selected_by_a).unwrap()
-                }),
-            }
+    let mut columns = vec![
+        ("id", "ID"),
+        ("name", "Name"),
+        ("position", "Pos"),
+        ("team", "Team"),
+        ("cost", "Cost"),
+        ("selected_by_percent", "Selected"),
+        ("form", "Form"),
+        ("total_points", "Points"),
+    ];
+    columns.extend(
+        options
+            .columns
+            .iter()
+            .map(|stat| (stat.key(), stat.title())),
+    );
+    columns.push(("news", "News"));
+    let mut table = Table::new(&columns)
+        .max_width("name", 20)
+        .max_width("team", 16)
+        .max_width("news", 30);
+    for player in players.iter().take(options.limit) {
+        let team_name = team_map
+            .get(&player.team)
+            .map(|s| s.as_str())
+            .unwrap_or("Unknown");
 
-            println!(
-                "{:<4} {:<20} {:<4} {:<16} {:<8} {:<8} {:<8} {:<8} {:<30}",
-                "ID", "Name", "Pos", "Team", "Cost", "Selected", "Form", "Points", "News"
+        let mut row = vec![
+            json!(player.id),
+            json!(player.web_name),
+            json!(position_name(player.element_type)),
+            json!(team_name),
+            json!(player.now_cost as f64 / 10.0),
+            number_or_string(&player.selected_by_percent),
+            number_or_string(&player.form),
+            json!(player.total_points),
+        ];
+        row.extend(options.columns.iter().map(|&stat| stat_cell(stat, player)));
+        row.push(json!(player.news));
+        table.push(row);
+    }
+    table.print(ctx.output);
+    Ok(())
+}
+
+pub async fn handle_pick(
+    ctx: &Context,
+    manager_id: u64,
+    event: EventSelector,
+) -> Result<(), FplError> {
+    let bootstrap_data = ctx.client.fetch_bootstrap_static().await?;
+    let player_map = create_player_map(&bootstrap_data.elements);
+    let event_id = resolve_event(&bootstrap_data.events, event)? as u32;
+
+    let live_data = ctx.client.fetch_live(event_id).await?;
+    let points_map: HashMap<u64, i64> = live_data
+        .elements
+        .iter()
+        .map(|element| (element.id, element.stats.total_points))
+        .collect();
+
+    let picks = ctx.client.fetch_manager_picks(manager_id, event_id).await?;
+    let mut table = Table::new(&[
+        ("id", "ID"),
+        ("name", "Name"),
+        ("position", "Pos"),
+        ("is_captain", "C"),
+        ("is_vice_captain", "VC"),
+        ("points", "Pts"),
+    ])
+    .max_width("name", 20);
+    for pick in picks.picks.iter() {
+        let name = player_map
+            .get(&pick.element)
+            .map(|s| s.as_str())
+            .unwrap_or("Unknown");
+
+        let points = points_map.get(&pick.element).copied().unwrap_or(0);
+
+        table.push(vec![
+            json!(pick.element),
+            json!(name),
+            json!(pick.position),
+            json!(pick.is_captain),
+            json!(pick.is_vice_captain),
+            json!(points),
+        ]);
+    }
+    table.print(ctx.output);
+    Ok(())
+}
+
+/// Resolves a player ID or name, only fetching bootstrap data for names.
+async fn resolve_player_id(ctx: &Context, player: &str) -> Result<u64, FplError> {
+    if let Ok(id) = player.trim().parse::<u64>() {
+        return Ok(id);
+    }
+    let data = ctx.client.fetch_bootstrap_static().await?;
+    Ok(resolve_player(&data.elements, player)?.id)
+}
+
+pub async fn handle_search(ctx: &Context, query: &str, limit: usize) -> Result<(), FplError> {
+    let data = ctx.client.fetch_bootstrap_static().await?;
+    let team_map = create_team_map(&data.teams);
+    let mut table = Table::new(&[
+        ("id", "ID"),
+        ("web_name", "Name"),
+        ("full_name", "Full Name"),
+        ("position", "Pos"),
+        ("team", "Team"),
+        ("cost", "Cost"),
+        ("total_points", "Points"),
+    ]);
+    for (player, _) in search_players(&data.elements, query)
+        .into_iter()
+        .take(limit)
+    {
+        let position = position_name(player.element_type);
+        let team_name = team_map
+            .get(&player.team)
+            .map(|s| s.as_str())
+            .unwrap_or("Unknown");
+        table.push(vec![
+            json!(player.id),
+            json!(player.web_name),
+            json!(format!("{} {}", player.first_name, player.second_name)),
+            json!(position),
+            json!(team_name),
+            json!(player.now_cost as f64 / 10.0),
+            json!(player.total_points),
+        ]);
+    }
+    table.print(ctx.output);
+    Ok(())
+}
+
+pub struct PlayerSummaryOptions {
+    pub columns: Vec<HistoryStat>,
+    /// Metrics to chart instead of printing the history table.
+    pub graph: Vec<GraphMetric>,
+    pub rolling: Option<usize>,
+    pub fixtures: bool,
+    pub past: bool,
+}
+
+pub async fn handle_player_summary(
+    ctx: &Context,
+    player: &str,
+    options: PlayerSummaryOptions,
+) -> Result<(), FplError> {
+    let player_id = resolve_player_id(ctx, player).await?;
+    let summary = ctx.client.fetch_player_summary(player_id).await?;
+    let histories = summary.history;
+
+    if !options.graph.is_empty() {
+        // Charts only make sense in a terminal; other formats get the
+        // charted values instead.
+        if ctx.output == OutputFormat::Table {
+            print_charts(&histories, &options.graph, options.rolling);
+        } else {
+            series_table(&histories, &options.graph, options.rolling).print(ctx.output);
+        }
+    } else {
+        let data = ctx.client.fetch_bootstrap_static().await?;
+        let short_names = create_team_short_name_map(&data.teams);
+        let mut columns = vec![
+            ("round", "GW"),
+            ("opponent", "Opp"),
+            ("score", "Score"),
+            ("total_points", "Pts"),
+            ("minutes", "Min"),
+            ("goals_scored", "G"),
+            ("assists", "A"),
+        ];
+        columns.extend(
+            options
+                .columns
+                .iter()
+                .map(|stat| (stat.key(), stat.title())),
+        );
+        let mut table = Table::new(&columns);
+        for history in histories.iter() {
+            let mut row = vec![
+                json!(history.round),
+                json!(format_opponent(
+                    &short_names,
+                    history.opponent_team,
+                    history.was_home
+                )),
+                json!(format!("{}-{}", history.team_h_score, history.team_a_score)),
+                json!(history.total_points),
+                json!(history.minutes),
+                json!(history.goals_scored),
+                json!(history.assists),
+            ];
+            row.extend(
+                options
+                    .columns
+                    .iter()
+                    .map(|stat| history_cell(*stat, stat.value(history))),
             );
+            table.push(row);
+        }
 
-            for player in players.iter().take(limit) {
-                let team_name = team_map
-                    .get(&player.team)
-                    .map(|s| s.as_str())
-                    .unwrap_or("Unknown");
-
-                println!(
-                    "{:<4} {:<20} {:<4} {:<16} {:<8} {:<8} {:<8} {:<8} {:<30}",
-                    player.id,
-                    player.web_name,
-                    Position::from_element_type_id(player.element_type)
-                        .map(|p| p.display_name().to_string())
-                        .unwrap_or("N/A".to_string()),
-                    team_name,
-                    format!("{:.1}", player.now_cost as f64 / 10.0),
-                    player.selected_by_percent,
-                    player.form,
-                    player.total_points,
-                    player.news,
-                );
+        let mut totals = vec![
+            json!("Total"),
+            Value::Null,
+            Value::Null,
+            json!(histories.iter().map(|h| h.total_points).sum::<i64>()),
+            json!(histories.iter().map(|h| h.minutes).sum::<u64>()),
+            json!(histories.iter().map(|h| h.goals_scored).sum::<u64>()),
+            json!(histories.iter().map(|h| h.assists).sum::<u64>()),
+        ];
+        totals.extend(options.columns.iter().map(|stat| {
+            if stat.is_additive() {
+                let total: f64 = histories.iter().map(|h| stat.value(h)).sum();
+                history_cell(*stat, (total * 100.0).round() / 100.0)
+            } else {
+                Value::Null
             }
+        }));
+        table.footer(totals);
+
+        // The fixtures and past seasons follow the history as tables of
+        // their own.
+        let fixtures = options
+            .fixtures
+            .then(|| fixtures_table(ctx, &summary.fixtures, &data.teams));
+        let seasons = options.past.then(|| seasons_table(&summary.history_past));
+        let mut sections = vec![("history", "Gameweek history", &table)];
+        if let Some(fixtures) = &fixtures {
+            sections.push(("fixtures", "Remaining fixtures", fixtures));
         }
-        Err(e) => {
-            eprintln!("Error: {}", e);
+        if let Some(seasons) = &seasons {
+            sections.push(("past", "Previous seasons", seasons));
         }
+        print_sections(&sections, ctx.output);
     }
+    Ok(())
 }
 
-pub async fn handle_pick(manager_id: u64, event_id: u32) {
-    match FplClient::fetch_bootstrap_static().await {
-        Ok(bootstrap_data) => {
-            let player_map = create_player_map(&bootstrap_data.elements);
-
-            match FplClient::fetch_live(event_id).await {
-                Ok(live_data) => {
-                    let points_map: HashMap<u64, i64> = live_data
-                        .elements
-                        .iter()
-                        .map(|element| (element.id, element.stats.total_points))
-                        .collect();
-
-                    match FplClient::fetch_manager_picks(manager_id, event_id).await {
-                        Ok(picks) => {
-                            println!(
-                                "{:<4} {:<20} {:<4} {:<4} {:<4} {:<4}",
-                                "ID", "Name", "Pos", "C", "VC", "Pts"
-                            );
-                            for pick in picks.picks.iter() {
-                                let name = player_map
-                                    .get(&pick.element)
-                                    .map(|s| s.as_str())
-                                    .unwrap_or("Unknown");
-
-                                let points = points_map.get(&pick.element).copied().unwrap_or(0);
-
-                                println!(
-                                    "{:<4} {:<20} {:<4} {:<4} {:<4} {:<4}",
-                                    pick.element,
-                                    name,
-                                    pick.position,
-                                    if pick.is_captain { "Y" } else { "N" },
-                                    if pick.is_vice_captain { "Y" } else { "N" },
-                                    points,
-                                );
-                            }
-                        }
-                        Err(e) => {
-                            eprintln!("Error: {}", e);
-                        }
-                    }
-                }
-                Err(e) => {
-                    eprintln!("Error: {}", e);
-                }
-            }
-        }
-        Err(e) => {
-            eprintln!("Error: {}", e);
-        }
+fn fixtures_table(ctx: &Context, fixtures: &[PlayerFixture], teams: &[Team]) -> Table {
+    let short_names = create_team_short_name_map(teams);
+    let mut table = Table::new(&[
+        ("event", "GW"),
+        ("kickoff_time", "Kickoff Time"),
+        ("opponent", "Opponent"),
+        ("venue", "H/A"),
+        ("difficulty", "FDR"),
+    ]);
+    for fixture in fixtures {
+        table.push(vec![
+            json!(fixture.event),
+            json!(format_kickoff(
+                &ctx.time,
+                fixture.event,
+                fixture.kickoff_time.as_deref()
+            )),
+            json!(short_names.get(&fixture.opponent()).copied().unwrap_or("?")),
+            json!(if fixture.is_home { "H" } else { "A" }),
+            json!(fixture.difficulty),
+        ]);
+    }
+    table
+}
+
+fn seasons_table(seasons: &[PlayerSeason]) -> Table {
+    let mut table = Table::new(&[
+        ("season_name", "Season"),
+        ("start_cost", "Start"),
+        ("end_cost", "End"),
+        ("total_points", "Pts"),
+        ("minutes", "Min"),
+        ("starts", "Starts"),
+        ("goals_scored", "G"),
+        ("assists", "A"),
+        ("clean_sheets", "CS"),
+        ("bonus", "Bonus"),
+        ("expected_goals", "xG"),
+        ("expected_assists", "xA"),
+    ]);
+    for season in seasons {
+        table.push(vec![
+            json!(season.season_name),
+            json!(season.start_cost as f64 / 10.0),
+            json!(season.end_cost as f64 / 10.0),
+            json!(season.total_points),
+            json!(season.minutes),
+            json!(season.starts),
+            json!(season.goals_scored),
+            json!(season.assists),
+            json!(season.clean_sheets),
+            json!(season.bonus),
+            number_or_string(&season.expected_goals),
+            number_or_string(&season.expected_assists),
+        ]);
     }
+    table
 }
 
-pub async fn handle_player_summary(player_id: u64, show_graph: bool) {
-    match FplClient::fetch_player_summary(player_id).await {
-        Ok(summary) => {
-            let histories = summary.history;
+/// FPL's difficulty colours as (text, background), from 1 (easiest) to 5.
+const FDR_COLOURS: [([u8; 3], [u8; 3]); 5] = [
+    ([255, 255, 255], [0x37, 0x55, 0x23]),
+    ([0, 0, 0], [0x01, 0xfc, 0x7a]),
+    ([0, 0, 0], [0xe7, 0xe7, 0xe7]),
+    ([255, 255, 255], [0xff, 0x17, 0x51]),
+    ([255, 255, 255], [0x80, 0x07, 0x2d]),
+];
 
-            if show_graph {
-                let points_data: Vec<(f32, f32)> = histories
-                    .iter()
-                    .map(|h| (h.round as f32, h.total_points as f32))
-                    .collect();
-
-                if !points_data.is_empty() {
-                    println!("\nPoints per Gameweek:");
-                    Chart::new_with_y_range(120, 60, 1.0, points_data.len() as f32, 0.0, 20.0)
-                        .lineplot(&Shape::Lines(&points_data))
-                        .display();
-                }
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum TickerSort {
+    #[default]
+    Team,
+    /// Lowest summed difficulty first; blank gameweeks add nothing
+    Difficulty,
+    /// Lowest average difficulty per fixture first, so blanks never help
+    Average,
+}
+
+pub async fn handle_ticker(ctx: &Context, weeks: u64, sort: TickerSort) -> Result<(), FplError> {
+    let data = ctx.client.fetch_bootstrap_static().await?;
+    let last_event = data.events.iter().map(|e| e.id).max().unwrap_or(0);
+    let events = match data.events.iter().find(|e| e.is_next) {
+        Some(next) => next.id..=next.id.saturating_add(weeks.max(1) - 1).min(last_event),
+        None => {
+            eprintln!("There is no upcoming gameweek; the season is over.");
+            return Ok(());
+        }
+    };
+    let fixtures = ctx.client.fetch_fixtures().await?;
+    let short_names = create_team_short_name_map(&data.teams);
+
+    let mut rows = ticker_rows(&fixtures, &data.teams, events.clone());
+    match sort {
+        TickerSort::Team => rows.sort_by(|a, b| a.team.name.cmp(&b.team.name)),
+        TickerSort::Difficulty => {
+            rows.sort_by(|a, b| (a.total, &a.team.name).cmp(&(b.total, &b.team.name)));
+        }
+        TickerSort::Average => {
+            // Teams without a fixture in the whole run go last.
+            rows.sort_by(|a, b| {
+                let average = |row: &TickerRow| row.average.unwrap_or(f64::INFINITY);
+                average(a)
+                    .total_cmp(&average(b))
+                    .then_with(|| a.team.name.cmp(&b.team.name))
+            });
+        }
+    }
+
+    let event_columns: Vec<(String, String)> = events
+        .map(|event| (format!("gw{}", event), format!("GW{}", event)))
+        .collect();
+    let mut columns = vec![("team", "Team")];
+    columns.extend(
+        event_columns
+            .iter()
+            .map(|(key, title)| (key.as_str(), title.as_str())),
+    );
+    columns.push(("difficulty", "Total"));
+    columns.push(("average_difficulty", "Avg FDR"));
+    let mut table = Table::new(&columns);
+    for (r, ticker) in rows.iter().enumerate() {
+        let mut row = vec![json!(ticker.team.short_name)];
+        for (c, week) in ticker.weeks.iter().enumerate() {
+            // The difficulty is part of the text so it survives without
+            // colour and in other output formats.
+            let cells: Vec<String> = week
+                .iter()
+                .map(|(opponent, home, difficulty)| {
+                    format!(
+                        "{} {}",
+                        format_opponent(&short_names, *opponent, *home),
+                        difficulty
+                    )
+                })
+                .collect();
+            row.push(json!(if cells.is_empty() {
+                "-".to_string()
             } else {
-                println!(
-                    "{:<3} {:<3} {:<4} {:<2} {:<2}",
-                    "GW", "Pts", "Min", "G", "A"
-                );
-                for history in histories.iter() {
-                    println!(
-                        "{:<3} {:<3} {:<4} {:<2} {:<2}",
-                        history.round,
-                        history.total_points,
-                        history.minutes,
-                        history.goals_scored,
-                        history.assists
-                    );
-                }
+                cells.join(", ")
+            }));
+            // Double gameweeks take the colour of their harder fixture.
+            if let Some(difficulty) = week.iter().map(|(_, _, d)| *d).max() {
+                let (fg, bg) = FDR_COLOURS[difficulty.clamp(1, 5) as usize - 1];
+                table.style(r, c + 1, CellStyle::Colour { fg, bg });
             }
         }
-        Err(e) => {
-            eprintln!("Error: {}", e);
-        }
+        row.push(json!(ticker.total));
+        row.push(json!(
+            ticker
+                .average
+                .map(|average| (average * 10.0).round() / 10.0)
+        ));
+        table.push(row);
     }
+    table.print(ctx.output);
+    Ok(())
 }
 
-pub async fn handle_team() {
-    match FplClient::fetch_bootstrap_static().await {
-        Ok(data) => {
-            println!(
-                "{:<4} {:<20} {:<8} {:<8}",
-                "ID", "Name", "Short", "Strength"
-            );
-            for team in data.teams {
-                println!(
-                    "{:<4} {:<20} {:<8} {:<8}",
-                    team.id, team.name, team.short_name, team.strength
+pub async fn handle_team(ctx: &Context) -> Result<(), FplError> {
+    let data = ctx.client.fetch_bootstrap_static().await?;
+    let mut table = Table::new(&[
+        ("id", "ID"),
+        ("name", "Name"),
+        ("short_name", "Short"),
+        ("strength", "Strength"),
+    ]);
+    for team in data.teams {
+        table.push(vec![
+            json!(team.id),
+            json!(team.name),
+            json!(team.short_name),
+            json!(team.strength),
+        ]);
+    }
+    table.print(ctx.output);
+    Ok(())
+}
+
+pub struct FixtureOptions {
+    pub event: Option<u64>,
+    pub from: Option<u64>,
+    pub to: Option<u64>,
+    pub team: Option<String>,
+    /// Only finished fixtures, defaulting to the current gameweek.
+    pub results: bool,
+    /// Only postponed fixtures that have no gameweek yet.
+    pub unscheduled: bool,
+}
+
+pub async fn handle_fixture(ctx: &Context, options: FixtureOptions) -> Result<(), FplError> {
+    let bootstrap_data = ctx.client.fetch_bootstrap_static().await?;
+    let team_map = create_team_map(&bootstrap_data.teams);
+    let event_ids = bootstrap_data.events.iter().map(|e| e.id);
+    let (first_event, last_event) = (
+        event_ids.clone().min().unwrap_or(0),
+        event_ids.max().unwrap_or(0),
+    );
+    let current_event = bootstrap_data.events.iter().find(|e| e.is_current);
+    let next_event = bootstrap_data.events.iter().find(|e| e.is_next);
+
+    // The upcoming view also lists postponed fixtures, so they are not
+    // forgotten until they are rescheduled.
+    let mut postponed = false;
+    let (events, finished) = if options.unscheduled {
+        postponed = true;
+        (None, None)
+    } else if let Some(event) = options.event {
+        let events = event_range(&bootstrap_data.events, event, event)?;
+        (Some(events), options.results.then_some(true))
+    } else if options.from.is_some() || options.to.is_some() {
+        let from = options.from.unwrap_or(first_event);
+        let to = options.to.unwrap_or(last_event);
+        let events = event_range(&bootstrap_data.events, from, to)?;
+        (Some(events), options.results.then_some(true))
+    } else if options.results {
+        match current_event {
+            Some(event) => (Some(event.id..=event.id), Some(true)),
+            None => {
+                eprintln!("No gameweek has started yet, so there are no results.");
+                (None, None)
+            }
+        }
+    } else {
+        match next_event {
+            Some(event) => {
+                postponed = true;
+                (Some(event.id..=event.id), Some(false))
+            }
+            None => {
+                eprintln!(
+                    "There is no upcoming gameweek; the season is over. \
+                     Use --results or --event to see played fixtures."
                 );
+                (None, None)
             }
         }
-        Err(e) => {
-            eprintln!("Error: {}", e);
+    };
+    let team_ids = match &options.team {
+        Some(name) => resolve_team_ids(&bootstrap_data.teams, name)?,
+        None => Vec::new(),
+    };
+
+    let mut table = Table::new(&[
+        ("id", "ID"),
+        ("event", "GW"),
+        ("kickoff_time", "Kickoff Time"),
+        ("home", "Home"),
+        ("score", "Score"),
+        ("away", "Away"),
+    ]);
+    if events.is_some() || postponed {
+        let fixtures = ctx.client.fetch_fixtures().await?;
+        let mut selected = match events {
+            Some(events) => select_fixtures(&fixtures, events, &team_ids, finished),
+            None => Vec::new(),
+        };
+        if postponed {
+            selected.extend(unscheduled_fixtures(&fixtures, &team_ids));
+        }
+        for fixture in selected {
+            let team_name = |id: u64| team_map.get(&id).map(|s| s.as_str()).unwrap_or("Unknown");
+            let score = match (fixture.team_h_score, fixture.team_a_score) {
+                (Some(home), Some(away)) => json!(format!("{}-{}", home, away)),
+                _ => Value::Null,
+            };
+            table.push(vec![
+                json!(fixture.id),
+                json!(fixture.event),
+                json!(format_kickoff(
+                    &ctx.time,
+                    fixture.event,
+                    fixture.kickoff_time.as_deref()
+                )),
+                json!(team_name(fixture.team_h)),
+                score,
+                json!(team_name(fixture.team_a)),
+            ]);
         }
     }
+    table.print(ctx.output);
+    Ok(())
 }
 
-pub async fn handle_fixture() {
-    match FplClient::fetch_bootstrap_static().await {
-        Ok(bootstrap_data) => {
-            let team_map = create_team_map(&bootstrap_data.teams);
-
-            if let Some(next_event) = bootstrap_data.events.iter().find(|e| e.is_next) {
-                let next_event_id = next_event.id;
-
-                match FplClient::fetch_fixtures().await {
-                    Ok(fixtures_data) => {
-                        if let Some(fixtures) = fixtures_data.as_array() {
-                            let mut next_fixtures: Vec<_> = fixtures
-                                .iter()
-                                .filter_map(|fixture| {
-                                    let event = fixture["event"].as_u64()?;
-                                    if event != next_event_id {
-                                        return None;
-                                    }
-
-                                    let id = fixture["id"].as_u64()?;
-                                    let kickoff_time = fixture["kickoff_time"].as_str()?;
-                                    let team_a = fixture["team_a"].as_u64()?;
-                                    let team_h = fixture["team_h"].as_u64()?;
-                                    let finished = fixture["finished"].as_bool().unwrap_or(false);
-
-                                    if !finished {
-                                        Some((id, kickoff_time.to_string(), team_a, team_h))
-                                    } else {
-                                        None
-                                    }
-                                })
-                                .collect();
-
-                            next_fixtures.sort_by(|a, b| a.1.cmp(&b.1));
-                            println!(
-                                "{:<4} {:<20} {:<20} {:<20}",
-                                "ID", "Kickoff Time", "Home", "Away"
-                            );
-                            for (id, kickoff_time, team_h, team_a) in next_fixtures {
-                                let home_team = team_map
-                                    .get(&team_h)
-                                    .map(|s| s.as_str())
-                                    .unwrap_or("Unknown");
-                                let away_team = team_map
-                                    .get(&team_a)
-                                    .map(|s| s.as_str())
-                                    .unwrap_or("Unknown");
-                                println!(
-                                    "{:<4} {:<20} {:<20} {:<20}",
-                                    id,
-                                    format_datetime(&kickoff_time),
-                                    home_team,
-                                    away_team
-                                );
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        eprintln!("Error: {}", e);
-                    }
-                }
-            }
-        }
-        Err(e) => {
-            eprintln!("Error: {}", e);
-        }
+pub async fn handle_snapshot_save(
+    ctx: &Context,
+    dir: &Path,
+    options: &SnapshotOptions,
+) -> Result<(), FplError> {
+    let files = ctx.client.save_snapshot(dir, options).await?;
+    for file in &files {
+        println!("{}", file.display());
+    }
+    println!("Saved {} responses to {}", files.len(), dir.display());
+    Ok(())
+}
+
+pub fn handle_cache_status(cache: &Cache) -> Result<(), FplError> {
+    let entries = cache.entries()?;
+    let total: u64 = entries.iter().map(|entry| entry.size).sum();
+    println!(
+        "Cache: {} ({} entries, {:.1} MB)",
+        cache.dir().display(),
+        entries.len(),
+        total as f64 / (1024.0 * 1024.0)
+    );
+    println!(
+        "{:<8} {:<10} {:<6} {:<8} {:<60}",
+        "State", "Size", "Age", "Expires", "URL"
+    );
+    for entry in entries {
+        let (state, expires) = if entry.is_fresh() {
+            (
+                "fresh",
+                format_duration(entry.ttl_secs as i64 - entry.age_secs),
+            )
+        } else {
+            ("expired", "-".to_string())
+        };
+        println!(
+            "{:<8} {:<10} {:<6} {:<8} {:<60}",
+            state,
+            format!("{:.1} KB", entry.size as f64 / 1024.0),
+            format_duration(entry.age_secs),
+            expires,
+            entry.url
+        );
+    }
+    Ok(())
+}
+
+pub fn handle_cache_clear(cache: &Cache) -> Result<(), FplError> {
+    let removed = cache.clear()?;
+    println!("Removed {} cached responses", removed);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select_fixtures_keeps_home_and_away() {
+        let fixtures: Vec<Fixture> =
+            serde_json::from_str(include_str!("../tests/data/fixtures.json")).unwrap();
+        let upcoming = select_fixtures(&fixtures, 16..=16, &[], Some(false));
+
+        let ids: Vec<u64> = upcoming.iter().map(|f| f.id).collect();
+        assert_eq!(ids, vec![154, 153, 151, 155]);
+        // Everton (9) host Chelsea (7).
+        assert_eq!((upcoming[0].team_h, upcoming[0].team_a), (9, 7));
+        // Wolves (20) host Arsenal (1).
+        assert_eq!((upcoming[2].team_h, upcoming[2].team_a), (20, 1));
+    }
+
+    #[test]
+    fn test_select_fixtures_by_range_team_and_result() {
+        let fixtures: Vec<Fixture> =
+            serde_json::from_str(include_str!("../tests/data/fixtures.json")).unwrap();
+        let all = select_fixtures(&fixtures, 15..=17, &[], None);
+        assert!(all.windows(2).all(|w| w[0].event <= w[1].event));
+
+        let arsenal = select_fixtures(&fixtures, 15..=17, &[1], None);
+        assert!(!arsenal.is_empty());
+        assert!(arsenal.iter().all(|f| f.team_h == 1 || f.team_a == 1));
+
+        let results = select_fixtures(&fixtures, 15..=17, &[], Some(true));
+        assert!(!results.is_empty());
+        assert!(
+            results
+                .iter()
+                .all(|f| f.finished && f.team_h_score.is_some())
+        );
+    }
+
+    #[test]
+    fn test_postponed_and_undated_fixtures() {
+        let mut fixtures: Vec<Fixture> =
+            serde_json::from_str(include_str!("../tests/data/fixtures.json")).unwrap();
+        // 154 has lost its kickoff time and 153 has been postponed.
+        let undated = fixtures.iter().position(|f| f.id == 154).unwrap();
+        fixtures[undated].kickoff_time = None;
+        let postponed = fixtures.iter().position(|f| f.id == 153).unwrap();
+        fixtures[postponed].event = None;
+
+        let ids: Vec<u64> = select_fixtures(&fixtures, 16..=16, &[], Some(false))
+            .iter()
+            .map(|f| f.id)
+            .collect();
+        assert_eq!(ids, vec![151, 155, 154]);
+        let unscheduled = unscheduled_fixtures(&fixtures, &[]);
+        assert_eq!(unscheduled.len(), 1);
+        assert_eq!(unscheduled[0].id, 153);
+
+        let time = TimeFormat {
+            tz: chrono_tz::Tz::UTC,
+            format: TimeFormat::DEFAULT_FORMAT.to_string(),
+        };
+        assert_eq!(format_kickoff(&time, Some(16), None), "TBD");
+        assert_eq!(
+            format_kickoff(&time, None, Some("2024-12-14T15:00:00Z")),
+            "Postponed"
+        );
+        assert_eq!(
+            format_kickoff(&time, Some(16), Some("2024-12-14T15:00:00Z")),
+            "2024-12-14 15:00 UTC"
+        );
     }
 }
@@ -0,0 +1,164 @@
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+#[derive(Debug)]
+pub enum FplError {
+    /// The request could not be sent or the connection failed.
+    Network(reqwest::Error),
+    /// The API answered with a non-success status, e.g. 404 for an unknown manager.
+    Http {
+        status: u16,
+        url: String,
+    },
+    /// The response body did not match the expected model.
+    Decode {
+        url: String,
+        path: String,
+        message: String,
+    },
+    /// The API is unavailable while FPL updates the game.
+    GameUpdating,
+    /// The requested data is not part of the offline snapshot.
+    Offline {
+        path: String,
+        file: PathBuf,
+        source: io::Error,
+    },
+    /// The command needs a current gameweek, but the season has not started.
+    SeasonNotStarted,
+    /// The requested gameweek does not exist, e.g. `next` after the last one.
+    UnknownEvent(String),
+    /// A gameweek range ends before it starts.
+    InvalidEventRange {
+        from: u64,
+        to: u64,
+    },
+    /// No team name or short name contains the given text.
+    UnknownTeam(String),
+    /// No player matches the given ID or name.
+    UnknownPlayer(String),
+    /// A player name matches several players equally well.
+    AmbiguousPlayer {
+        query: String,
+        candidates: Vec<String>,
+    },
+    Io(io::Error),
+}
+
+impl FplError {
+    /// Whether retrying the same request later may succeed.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            FplError::Network(e) => e.is_timeout() || e.is_connect(),
+            FplError::Http { status, .. } => matches!(status, 429 | 500 | 502 | 504),
+            FplError::GameUpdating => true,
+            _ => false,
+        }
+    }
+
+    /// Process exit code for this category of error.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            FplError::Io(_) => 1,
+            FplError::Network(_) => 3,
+            FplError::Http { .. } => 4,
+            FplError::Decode { .. } => 5,
+            FplError::GameUpdating => 6,
+            FplError::Offline { .. } => 7,
+            FplError::SeasonNotStarted => 8,
+            FplError::UnknownEvent(_)
+            | FplError::InvalidEventRange { .. }
+            | FplError::UnknownTeam(_)
+            | FplError::UnknownPlayer(_)
+            | FplError::AmbiguousPlayer { .. } => 2,
+        }
+    }
+}
+
+impl fmt::Display for FplError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FplError::Network(e) => write!(f, "network error: {}", e),
+            FplError::Http { status: 404, url } => write!(f, "not found (HTTP 404): {}", url),
+            FplError::Http { status, url } => write!(f, "HTTP {} from {}", status, url),
+            FplError::Decode { url, path, message } if path.is_empty() || path == "." => {
+                write!(f, "unexpected response from {}: {}", url, message)
+            }
+            FplError::Decode { url, path, message } => {
+                write!(
+                    f,
+                    "unexpected response from {} at `{}`: {}",
+                    url, path, message
+                )
+            }
+            FplError::GameUpdating => write!(f, "FPL is updating, try again in a few minutes"),
+            FplError::Offline { path, file, source } => write!(
+                f,
+                "{} is not available offline ({}: {})",
+                path,
+                file.display(),
+                source
+            ),
+            FplError::SeasonNotStarted => {
+                write!(f, "the season has not started yet; no gameweek is current")
+            }
+            FplError::UnknownEvent(event) => write!(f, "no gameweek matches `{}`", event),
+            FplError::InvalidEventRange { from, to } => {
+                write!(f, "gameweek {} comes after gameweek {}", from, to)
+            }
+            FplError::UnknownTeam(name) => write!(f, "no team matches `{}`", name),
+            FplError::UnknownPlayer(query) => write!(f, "no player matches `{}`", query),
+            FplError::AmbiguousPlayer { query, candidates } => {
+                write!(f, "`{}` matches several players:", query)?;
+                for candidate in candidates {
+                    write!(f, "\n  {}", candidate)?;
+                }
+                write!(f, "\nuse the player ID or a more specific name")
+            }
+            FplError::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for FplError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FplError::Network(e) => Some(e),
+            FplError::Offline { source, .. } => Some(source),
+            FplError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for FplError {
+    fn from(e: reqwest::Error) -> Self {
+        FplError::Network(e)
+    }
+}
+
+impl From<io::Error> for FplError {
+    fn from(e: io::Error) -> Self {
+        FplError::Io(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_display_includes_path() {
+        let err = FplError::Decode {
+            url: "https://example.com/api/fixtures/".to_string(),
+            path: "[3].team_h".to_string(),
+            message: "invalid type: null, expected u64".to_string(),
+        };
+        assert_eq!(
+            err.to_string(),
+            "unexpected response from https://example.com/api/fixtures/ at `[3].team_h`: invalid type: null, expected u64"
+        );
+        assert_eq!(err.exit_code(), 5);
+    }
+}
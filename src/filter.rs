@@ -0,0 +1,480 @@
+//! A small expression language for filtering players, e.g.
+//! `cost <= 6.5 && form >= 5 && selected < 10 && status == "a"`.
+//!
+//! Comparisons (`==`, `!=`, `<`, `<=`, `>`, `>=`) take a field on the left
+//! and a number or quoted string on the right, and can be combined with
+//! `&&`, `||`, `!` and parentheses. Text comparisons ignore case.
+
+use std::fmt;
+use std::str::FromStr;
+
+use clap::ValueEnum;
+
+use crate::models::{Element, PlayerStat, Position, SortBy};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FilterError {
+    pub message: String,
+    /// Byte offset into the expression where the problem was found.
+    pub position: usize,
+}
+
+impl fmt::Display for FilterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (at position {})", self.message, self.position + 1)
+    }
+}
+
+impl std::error::Error for FilterError {}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Field {
+    Id,
+    Team,
+    /// Season values shared with `player --sort`.
+    Sort(SortBy),
+    Status,
+    Name,
+    News,
+    Position,
+    Stat(PlayerStat),
+}
+
+enum FieldValue {
+    Number(f64),
+    Text(String),
+}
+
+const NAMED_FIELDS: &[(&str, Field)] = &[
+    ("id", Field::Id),
+    ("team", Field::Team),
+    ("cost", Field::Sort(SortBy::Cost)),
+    ("price", Field::Sort(SortBy::Cost)),
+    ("selected", Field::Sort(SortBy::SelectedBy)),
+    ("ownership", Field::Sort(SortBy::SelectedBy)),
+    ("form", Field::Sort(SortBy::Form)),
+    ("points", Field::Sort(SortBy::Points)),
+    ("ppm", Field::Sort(SortBy::PointsPerMillion)),
+    ("status", Field::Status),
+    ("name", Field::Name),
+    ("news", Field::News),
+    ("position", Field::Position),
+    ("pos", Field::Position),
+];
+
+impl Field {
+    fn lookup(name: &str) -> Option<Self> {
+        let name = name.to_lowercase();
+        if let Some((_, field)) = NAMED_FIELDS.iter().find(|(n, _)| *n == name) {
+            return Some(*field);
+        }
+        PlayerStat::value_variants()
+            .iter()
+            .find(|stat| {
+                stat.key() == name
+                    || stat
+                        .to_possible_value()
+                        .is_some_and(|v| v.get_name() == name.replace('_', "-"))
+            })
+            .map(|stat| Field::Stat(*stat))
+    }
+
+    fn is_text(&self) -> bool {
+        matches!(
+            self,
+            Field::Status | Field::Name | Field::News | Field::Position
+        )
+    }
+
+    fn value(&self, element: &Element) -> FieldValue {
+        let number = match self {
+            Field::Id => element.id as f64,
+            Field::Team => element.team as f64,
+            Field::Sort(key) => key.value(element),
+            // No availability news means the player is fully fit.
+            Field::Stat(PlayerStat::Chance) => {
+                element.chance_of_playing_next_round.unwrap_or(100) as f64
+            }
+            Field::Stat(stat) => stat.value(element).unwrap_or(0.0),
+            Field::Status => return FieldValue::Text(element.status.clone()),
+            Field::Name => return FieldValue::Text(element.web_name.clone()),
+            Field::News => return FieldValue::Text(element.news.clone()),
+            Field::Position => {
+                let position = Position::from_element_type_id(element.element_type)
+                    .map(|p| p.display_name().to_string())
+                    .unwrap_or_default();
+                return FieldValue::Text(position);
+            }
+        };
+        FieldValue::Number(number)
+    }
+}
+
+fn field_names() -> String {
+    let mut names: Vec<String> = NAMED_FIELDS.iter().map(|(n, _)| n.to_string()).collect();
+    names.extend(
+        PlayerStat::value_variants()
+            .iter()
+            .filter_map(|stat| stat.to_possible_value())
+            .map(|v| v.get_name().replace('-', "_")),
+    );
+    names.join(", ")
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Literal {
+    Number(f64),
+    Text(String),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Compare {
+        field: Field,
+        op: Op,
+        value: Literal,
+    },
+}
+
+impl Expr {
+    fn matches(&self, element: &Element) -> bool {
+        match self {
+            Expr::And(a, b) => a.matches(element) && b.matches(element),
+            Expr::Or(a, b) => a.matches(element) || b.matches(element),
+            Expr::Not(e) => !e.matches(element),
+            Expr::Compare { field, op, value } => match (field.value(element), value) {
+                (FieldValue::Number(a), Literal::Number(b)) => match op {
+                    Op::Eq => a == *b,
+                    Op::Ne => a != *b,
+                    Op::Lt => a < *b,
+                    Op::Le => a <= *b,
+                    Op::Gt => a > *b,
+                    Op::Ge => a >= *b,
+                },
+                (FieldValue::Text(a), Literal::Text(b)) => {
+                    let equal = a.to_lowercase() == b.to_lowercase();
+                    if *op == Op::Eq { equal } else { !equal }
+                }
+                // Ruled out while parsing.
+                _ => false,
+            },
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Ident(String),
+    Number(f64),
+    Text(String),
+    Op(Op),
+    And,
+    Or,
+    Not,
+    LParen,
+    RParen,
+}
+
+fn tokenize(input: &str) -> Result<Vec<(usize, Token)>, FilterError> {
+    let mut tokens = Vec::new();
+    let mut chars = input.char_indices().peekable();
+    while let Some(&(start, c)) = chars.peek() {
+        let error = |message: String| FilterError {
+            message,
+            position: start,
+        };
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+        let token = if c.is_ascii_alphabetic() || c == '_' {
+            let mut ident = String::new();
+            while let Some(&(_, c)) = chars.peek() {
+                if !(c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+                    break;
+                }
+                ident.push(c);
+                chars.next();
+            }
+            match ident.to_lowercase().as_str() {
+                "and" => Token::And,
+                "or" => Token::Or,
+                "not" => Token::Not,
+                _ => Token::Ident(ident),
+            }
+        } else if c.is_ascii_digit() || c == '.' || c == '-' {
+            let mut number = String::new();
+            number.push(c);
+            chars.next();
+            while let Some(&(_, c)) = chars.peek() {
+                if !(c.is_ascii_digit() || c == '.') {
+                    break;
+                }
+                number.push(c);
+                chars.next();
+            }
+            Token::Number(
+                number
+                    .parse()
+                    .map_err(|_| error(format!("invalid number `{}`", number)))?,
+            )
+        } else if c == '"' || c == '\'' {
+            chars.next();
+            let mut text = String::new();
+            loop {
+                match chars.next() {
+                    Some((_, q)) if q == c => break,
+                    Some((_, ch)) => text.push(ch),
+                    None => return Err(error("unterminated string".to_string())),
+                }
+            }
+            Token::Text(text)
+        } else {
+            chars.next();
+            let next = chars.peek().map(|&(_, c)| c);
+            let (token, double) = match (c, next) {
+                ('=', Some('=')) => (Token::Op(Op::Eq), true),
+                ('!', Some('=')) => (Token::Op(Op::Ne), true),
+                ('<', Some('=')) => (Token::Op(Op::Le), true),
+                ('>', Some('=')) => (Token::Op(Op::Ge), true),
+                ('&', Some('&')) => (Token::And, true),
+                ('|', Some('|')) => (Token::Or, true),
+                ('=', _) => (Token::Op(Op::Eq), false),
+                ('<', _) => (Token::Op(Op::Lt), false),
+                ('>', _) => (Token::Op(Op::Gt), false),
+                ('!', _) => (Token::Not, false),
+                ('(', _) => (Token::LParen, false),
+                (')', _) => (Token::RParen, false),
+                _ => return Err(error(format!("unexpected character `{}`", c))),
+            };
+            if double {
+                chars.next();
+            }
+            token
+        };
+        tokens.push((start, token));
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    pos: usize,
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(_, token)| token)
+    }
+
+    fn offset(&self) -> usize {
+        self.tokens
+            .get(self.pos)
+            .map_or(self.end, |(offset, _)| *offset)
+    }
+
+    fn error(&self, message: String) -> FilterError {
+        FilterError {
+            message,
+            position: self.offset(),
+        }
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.peek().cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn or(&mut self) -> Result<Expr, FilterError> {
+        let mut expr = self.and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.next();
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, FilterError> {
+        let mut expr = self.unary()?;
+        while self.peek() == Some(&Token::And) {
+            self.next();
+            expr = Expr::And(Box::new(expr), Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr, FilterError> {
+        match self.peek() {
+            Some(Token::Not) => {
+                self.next();
+                Ok(Expr::Not(Box::new(self.unary()?)))
+            }
+            Some(Token::LParen) => {
+                self.next();
+                let expr = self.or()?;
+                if self.peek() != Some(&Token::RParen) {
+                    return Err(self.error("expected `)`".to_string()));
+                }
+                self.next();
+                Ok(expr)
+            }
+            _ => self.comparison(),
+        }
+    }
+
+    fn comparison(&mut self) -> Result<Expr, FilterError> {
+        let field_offset = self.offset();
+        let field = match self.next() {
+            Some(Token::Ident(name)) => Field::lookup(&name).ok_or_else(|| FilterError {
+                message: format!("unknown field `{}`; known fields: {}", name, field_names()),
+                position: field_offset,
+            })?,
+            _ => {
+                self.pos -= 1;
+                return Err(self.error("expected a field name".to_string()));
+            }
+        };
+        let op = match self.next() {
+            Some(Token::Op(op)) => op,
+            _ => {
+                self.pos -= 1;
+                return Err(self.error("expected a comparison operator".to_string()));
+            }
+        };
+        let value_offset = self.offset();
+        let value = match self.next() {
+            Some(Token::Number(n)) => Literal::Number(n),
+            Some(Token::Text(s)) => Literal::Text(s),
+            // Allow unquoted words for text fields, e.g. `status == a`.
+            Some(Token::Ident(s)) if field.is_text() => Literal::Text(s),
+            _ => {
+                self.pos -= 1;
+                return Err(self.error("expected a number or a quoted string".to_string()));
+            }
+        };
+
+        let type_error = |message: String| FilterError {
+            message,
+            position: value_offset,
+        };
+        match (&value, field.is_text()) {
+            (Literal::Text(_), false) => {
+                return Err(type_error(
+                    "this field is numeric; expected a number".to_string(),
+                ));
+            }
+            (Literal::Number(_), true) => {
+                return Err(type_error(
+                    "this field is text; expected a quoted string".to_string(),
+                ));
+            }
+            (Literal::Text(_), true) if !matches!(op, Op::Eq | Op::Ne) => {
+                return Err(type_error(
+                    "text fields only support `==` and `!=`".to_string(),
+                ));
+            }
+            _ => {}
+        }
+        Ok(Expr::Compare { field, op, value })
+    }
+}
+
+/// A parsed `--where` expression.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Filter {
+    expr: Expr,
+}
+
+impl Filter {
+    pub fn parse(input: &str) -> Result<Self, FilterError> {
+        let mut parser = Parser {
+            tokens: tokenize(input)?,
+            pos: 0,
+            end: input.len(),
+        };
+        let expr = parser.or()?;
+        if parser.pos < parser.tokens.len() {
+            return Err(parser.error("unexpected input".to_string()));
+        }
+        Ok(Filter { expr })
+    }
+
+    pub fn matches(&self, element: &Element) -> bool {
+        self.expr.matches(element)
+    }
+}
+
+impl FromStr for Filter {
+    type Err = FilterError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Filter::parse(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn haaland() -> Element {
+        serde_json::from_str(include_str!("../tests/data/element.json")).unwrap()
+    }
+
+    fn matches(input: &str) -> bool {
+        Filter::parse(input).unwrap().matches(&haaland())
+    }
+
+    #[test]
+    fn test_comparisons() {
+        assert!(matches("cost >= 15"));
+        assert!(!matches("cost <= 6.5"));
+        assert!(matches("form > 4 && selected >= 50 && status == \"a\""));
+        assert!(matches("xg > 10 and minutes >= 1000"));
+        assert!(matches("expected_goals > 10"));
+        assert!(matches("pos == 'fwd' && chance == 100"));
+        assert!(matches("status == a"));
+    }
+
+    #[test]
+    fn test_precedence_and_grouping() {
+        assert!(matches("cost < 5 || points > 100 && status == 'a'"));
+        assert!(!matches("(cost < 5 || points > 100) && status == 'i'"));
+        assert!(matches("!(cost < 5)"));
+        assert!(matches("not status != 'a'"));
+    }
+
+    #[test]
+    fn test_errors() {
+        let err = Filter::parse("cost <= 6.5 && ownage < 10").unwrap_err();
+        assert!(err.message.starts_with("unknown field `ownage`"));
+        assert_eq!(err.position, 15);
+
+        let err = Filter::parse("status < 'a'").unwrap_err();
+        assert_eq!(err.message, "text fields only support `==` and `!=`");
+
+        let err = Filter::parse("cost <= 'cheap'").unwrap_err();
+        assert_eq!(err.message, "this field is numeric; expected a number");
+
+        let err = Filter::parse("(cost <= 5").unwrap_err();
+        assert_eq!(err.message, "expected `)`");
+        assert_eq!(err.position, 10);
+
+        assert!(Filter::parse("cost <= 5 form").is_err());
+        assert!(Filter::parse("status == \"a").is_err());
+    }
+}
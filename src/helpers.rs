@@ -0,0 +1,382 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+
+use crate::error::FplError;
+use crate::models::{Element, Event, EventSelector, Fixture, Position, SortBy, Team};
+
+/// Maps team IDs to team names.
+pub fn create_team_map(teams: &[Team]) -> HashMap<u64, String> {
+    teams
+        .iter()
+        .map(|team| (team.id, team.name.clone()))
+        .collect()
+}
+
+/// Maps team IDs to short names such as `ARS`.
+pub fn create_team_short_name_map(teams: &[Team]) -> HashMap<u64, &str> {
+    teams
+        .iter()
+        .map(|team| (team.id, team.short_name.as_str()))
+        .collect()
+}
+
+/// Formats an opponent as its short name and venue, e.g. `ARS (H)`.
+pub fn format_opponent(short_names: &HashMap<u64, &str>, opponent: u64, home: bool) -> String {
+    format!(
+        "{} ({})",
+        short_names.get(&opponent).copied().unwrap_or("?"),
+        if home { "H" } else { "A" }
+    )
+}
+
+/// Short position name for an `element_type`, or `N/A` if it is unknown.
+pub fn position_name(element_type: u64) -> &'static str {
+    Position::from_element_type_id(element_type).map_or("N/A", |p| p.display_name())
+}
+
+/// IDs of teams whose name or short name contains `name`, case-insensitively.
+pub fn find_team_ids_by_name(teams: &[Team], name: &str) -> Vec<u64> {
+    let search_term = name.to_lowercase();
+    teams
+        .iter()
+        .filter(|team| {
+            team.name.to_lowercase().contains(&search_term)
+                || team.short_name.to_lowercase().contains(&search_term)
+        })
+        .map(|team| team.id)
+        .collect()
+}
+
+/// Like [`find_team_ids_by_name`], but fails when no team matches.
+pub fn resolve_team_ids(teams: &[Team], name: &str) -> Result<Vec<u64>, FplError> {
+    let ids = find_team_ids_by_name(teams, name);
+    if ids.is_empty() {
+        return Err(FplError::UnknownTeam(name.to_string()));
+    }
+    Ok(ids)
+}
+
+/// Maps player IDs to their display (`web_name`) names.
+pub fn create_player_map(elements: &[Element]) -> HashMap<u64, String> {
+    elements
+        .iter()
+        .map(|player| (player.id, player.web_name.clone()))
+        .collect()
+}
+
+/// A team's fixtures in gameweek `event` from its own point of view, as
+/// (opponent, is home, difficulty). Double gameweeks give several entries.
+pub fn team_fixtures(fixtures: &[Fixture], team_id: u64, event: u64) -> Vec<(u64, bool, u8)> {
+    fixtures
+        .iter()
+        .filter(|f| f.event == Some(event))
+        .filter_map(|f| {
+            if f.team_h == team_id {
+                Some((f.team_a, true, f.team_h_difficulty))
+            } else if f.team_a == team_id {
+                Some((f.team_h, false, f.team_a_difficulty))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Resolves `selector` to the ID of one of `events`. Relative selectors
+/// need a current gameweek, so they fail before the season starts.
+pub fn resolve_event(events: &[Event], selector: EventSelector) -> Result<u64, FplError> {
+    let current = || {
+        events
+            .iter()
+            .find(|e| e.is_current)
+            .map(|e| e.id)
+            .ok_or(FplError::SeasonNotStarted)
+    };
+    let id = match selector {
+        EventSelector::Id(id) => Some(id),
+        EventSelector::Current => Some(current()?),
+        EventSelector::Next => events.iter().find(|e| e.is_next).map(|e| e.id),
+        EventSelector::Offset(offset) => current()?.checked_add_signed(offset),
+    };
+    id.filter(|id| events.iter().any(|e| e.id == *id))
+        .ok_or_else(|| FplError::UnknownEvent(selector.to_string()))
+}
+
+/// The gameweeks `from..=to`, checking that both ends are gameweeks of
+/// `events` and that the range is not empty.
+pub fn event_range(events: &[Event], from: u64, to: u64) -> Result<RangeInclusive<u64>, FplError> {
+    for id in [from, to] {
+        if !events.iter().any(|e| e.id == id) {
+            return Err(FplError::UnknownEvent(id.to_string()));
+        }
+    }
+    if from > to {
+        return Err(FplError::InvalidEventRange { from, to });
+    }
+    Ok(from..=to)
+}
+
+/// Gameweeks of `events` in which a team of `team_ids` plays other than
+/// once, as (event, team, number of fixtures): zero for a blank gameweek,
+/// two or more for a double.
+pub fn blank_and_double_gameweeks(
+    fixtures: &[Fixture],
+    team_ids: &[u64],
+    events: RangeInclusive<u64>,
+) -> Vec<(u64, u64, usize)> {
+    events
+        .flat_map(|event| {
+            team_ids.iter().filter_map(move |&team| {
+                let count = team_fixtures(fixtures, team, event).len();
+                (count != 1).then_some((event, team, count))
+            })
+        })
+        .collect()
+}
+
+/// A team's fixtures over a window of gameweeks, as shown by `ticker`.
+pub struct TickerRow<'a> {
+    pub team: &'a Team,
+    /// (opponent, is home, difficulty) per fixture, for each gameweek.
+    pub weeks: Vec<Vec<(u64, bool, u8)>>,
+    /// Summed difficulty of the run; blank gameweeks add nothing.
+    pub total: u64,
+    /// Average difficulty per fixture, so a blank gameweek never makes a
+    /// run look easier; `None` without any fixtures.
+    pub average: Option<f64>,
+}
+
+/// One [`TickerRow`] per team over gameweeks `events`.
+pub fn ticker_rows<'a>(
+    fixtures: &[Fixture],
+    teams: &'a [Team],
+    events: RangeInclusive<u64>,
+) -> Vec<TickerRow<'a>> {
+    teams
+        .iter()
+        .map(|team| {
+            let weeks: Vec<_> = events
+                .clone()
+                .map(|event| team_fixtures(fixtures, team.id, event))
+                .collect();
+            let difficulties: Vec<u64> = weeks
+                .iter()
+                .flatten()
+                .map(|(_, _, difficulty)| *difficulty as u64)
+                .collect();
+            let total = difficulties.iter().sum();
+            let average =
+                (!difficulties.is_empty()).then(|| total as f64 / difficulties.len() as f64);
+            TickerRow {
+                team,
+                weeks,
+                total,
+                average,
+            }
+        })
+        .collect()
+}
+
+/// Sorts `items` by each of `keys` in turn, later keys breaking ties.
+/// Highest values come first unless `ascending` is set.
+pub fn sort_by_keys<T>(
+    items: &mut [T],
+    keys: &[SortBy],
+    ascending: bool,
+    value: impl Fn(&T, &SortBy) -> f64,
+) {
+    items.sort_by(|a, b| {
+        keys.iter().fold(Ordering::Equal, |ordering, key| {
+            ordering.then_with(|| {
+                let ordering = value(a, key).total_cmp(&value(b, key));
+                if ascending {
+                    ordering
+                } else {
+                    ordering.reverse()
+                }
+            })
+        })
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn team(id: u64, name: &str, short_name: &str) -> Team {
+        serde_json::from_value(serde_json::json!({
+            "id": id, "name": name, "short_name": short_name,
+            "position": 0, "played": 0, "win": 0, "draw": 0, "loss": 0, "points": 0,
+            "strength": 3, "strength_overall_home": 0, "strength_overall_away": 0,
+            "strength_attack_home": 0, "strength_attack_away": 0,
+            "strength_defence_home": 0, "strength_defence_away": 0, "form": null
+        }))
+        .unwrap()
+    }
+
+    /// A 38-gameweek season that has not started yet.
+    fn events() -> Vec<Event> {
+        (1..=38)
+            .map(|id| {
+                serde_json::from_value(serde_json::json!({
+                    "id": id, "name": format!("Gameweek {}", id),
+                    "is_current": false, "is_next": false,
+                    "deadline_time": "2025-08-15T17:30:00Z", "finished": false
+                }))
+                .unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_sort_by_keys() {
+        // (form, points)
+        let mut rows = vec![(5.0, 10.0), (7.0, 3.0), (5.0, 40.0), (1.0, 99.0)];
+        let value = |row: &(f64, f64), key: &SortBy| match key {
+            SortBy::Form => row.0,
+            _ => row.1,
+        };
+
+        sort_by_keys(&mut rows, &[SortBy::Form, SortBy::Points], false, value);
+        assert_eq!(
+            rows,
+            vec![(7.0, 3.0), (5.0, 40.0), (5.0, 10.0), (1.0, 99.0)]
+        );
+
+        sort_by_keys(&mut rows, &[SortBy::Form, SortBy::Points], true, value);
+        assert_eq!(
+            rows,
+            vec![(1.0, 99.0), (5.0, 10.0), (5.0, 40.0), (7.0, 3.0)]
+        );
+    }
+
+    #[test]
+    fn test_find_team_ids_by_name() {
+        let teams = vec![
+            team(13, "Man City", "MCI"),
+            team(14, "Man Utd", "MUN"),
+            team(18, "Spurs", "TOT"),
+        ];
+        assert_eq!(find_team_ids_by_name(&teams, "man"), vec![13, 14]);
+        assert_eq!(find_team_ids_by_name(&teams, "tot"), vec![18]);
+        assert!(find_team_ids_by_name(&teams, "arsenal").is_empty());
+        assert_eq!(resolve_team_ids(&teams, "spurs").unwrap(), vec![18]);
+        assert!(matches!(
+            resolve_team_ids(&teams, "arsenal"),
+            Err(FplError::UnknownTeam(name)) if name == "arsenal"
+        ));
+    }
+
+    #[test]
+    fn test_format_opponent() {
+        let teams = vec![team(1, "Arsenal", "ARS"), team(20, "Wolves", "WOL")];
+        let short_names = create_team_short_name_map(&teams);
+        assert_eq!(format_opponent(&short_names, 20, true), "WOL (H)");
+        assert_eq!(format_opponent(&short_names, 1, false), "ARS (A)");
+        assert_eq!(format_opponent(&short_names, 99, false), "? (A)");
+        assert_eq!(position_name(3), "MID");
+        assert_eq!(position_name(9), "N/A");
+    }
+
+    #[test]
+    fn test_team_fixtures() {
+        let fixtures: Vec<Fixture> =
+            serde_json::from_str(include_str!("../tests/data/fixtures.json")).unwrap();
+        // Wolves (20) host Arsenal (1) in GW16.
+        let wolves = team_fixtures(&fixtures, 20, 16);
+        let arsenal = team_fixtures(&fixtures, 1, 16);
+        assert_eq!(wolves.len(), 1);
+        assert_eq!((wolves[0].0, wolves[0].1), (1, true));
+        assert_eq!((arsenal[0].0, arsenal[0].1), (20, false));
+        assert_eq!((wolves[0].2, arsenal[0].2), (5, 2));
+        assert!(team_fixtures(&fixtures, 20, 99).is_empty());
+    }
+
+    #[test]
+    fn test_blank_and_double_gameweeks() {
+        let mut fixtures: Vec<Fixture> =
+            serde_json::from_str(include_str!("../tests/data/fixtures.json")).unwrap();
+        // Arsenal (1) and Wolves (20) play each other in GW16, and Arsenal
+        // also plays Everton in GW17.
+        let team_ids = [1, 20];
+        assert!(blank_and_double_gameweeks(&fixtures, &team_ids, 16..=16).is_empty());
+
+        // Moving Wolves v Arsenal to GW17 blanks both teams in GW16 and
+        // gives Arsenal a double in GW17.
+        let moved = fixtures.iter_mut().find(|f| f.id == 151).unwrap();
+        moved.event = Some(17);
+        assert_eq!(
+            blank_and_double_gameweeks(&fixtures, &team_ids, 16..=17),
+            vec![(16, 1, 0), (16, 20, 0), (17, 1, 2)]
+        );
+    }
+
+    #[test]
+    fn test_resolve_event() {
+        let mut events = events();
+        events[0].is_next = true;
+        let resolve = |events: &[Event], arg: &str| resolve_event(events, arg.parse().unwrap());
+
+        assert!(matches!(
+            resolve(&events, "current"),
+            Err(FplError::SeasonNotStarted)
+        ));
+        assert_eq!(resolve(&events, "next").unwrap(), 1);
+        assert_eq!(resolve(&events, "7").unwrap(), 7);
+
+        events[9].is_current = true;
+        events[10].is_next = true;
+        events[0].is_next = false;
+        assert_eq!(resolve(&events, "current").unwrap(), 10);
+        assert_eq!(resolve(&events, "Next").unwrap(), 11);
+        assert_eq!(resolve(&events, "last").unwrap(), 9);
+        assert_eq!(resolve(&events, "+2").unwrap(), 12);
+        assert_eq!(resolve(&events, "-3").unwrap(), 7);
+        assert!(matches!(
+            resolve(&events, "-10"),
+            Err(FplError::UnknownEvent(e)) if e == "-10"
+        ));
+        assert!(matches!(
+            resolve(&events, "39"),
+            Err(FplError::UnknownEvent(_))
+        ));
+        assert!("soon".parse::<EventSelector>().is_err());
+        assert!("+x".parse::<EventSelector>().is_err());
+    }
+
+    #[test]
+    fn test_ticker_rows() {
+        let fixtures: Vec<Fixture> =
+            serde_json::from_str(include_str!("../tests/data/fixtures.json")).unwrap();
+        let teams = vec![
+            team(1, "Arsenal", "ARS"),
+            team(20, "Wolves", "WOL"),
+            team(99, "Nowhere", "NOW"),
+        ];
+        let rows = ticker_rows(&fixtures, &teams, 15..=17);
+        assert_eq!(rows[0].average, Some(2.5));
+        assert_eq!(rows[1].average, Some(5.0));
+        // A team without fixtures has no average rather than an easy zero.
+        assert_eq!((rows[2].total, rows[2].average), (0, None));
+    }
+
+    #[test]
+    fn test_event_range() {
+        let events = events();
+        assert_eq!(event_range(&events, 36, 38).unwrap(), 36..=38);
+        assert_eq!(event_range(&events, 38, 38).unwrap(), 38..=38);
+        assert!(matches!(
+            event_range(&events, 38, 40),
+            Err(FplError::UnknownEvent(e)) if e == "40"
+        ));
+        assert!(matches!(
+            event_range(&events, 0, 3),
+            Err(FplError::UnknownEvent(_))
+        ));
+        assert!(matches!(
+            event_range(&events, 10, 5),
+            Err(FplError::InvalidEventRange { from: 10, to: 5 })
+        ));
+    }
+}
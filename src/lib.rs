@@ -0,0 +1,27 @@
+//! Client and data models for the [Fantasy Premier League](https://fantasy.premierleague.com/) API.
+//!
+//! ```no_run
+//! use fplr::FplClient;
+//!
+//! # async fn run() -> Result<(), fplr::FplError> {
+//! let client = FplClient::default();
+//! let data = client.fetch_bootstrap_static().await?;
+//! let teams = fplr::helpers::create_team_map(&data.teams);
+//! for player in data.elements.iter().take(5) {
+//!     println!("{} ({})", player.web_name, teams[&player.team]);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod api;
+pub mod cache;
+pub mod calendar;
+pub mod error;
+pub mod filter;
+pub mod helpers;
+pub mod models;
+pub mod search;
+
+pub use api::FplClient;
+pub use error::FplError;
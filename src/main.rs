@@ -1,82 +1,374 @@
-mod api;
+mod chart;
 mod commands;
-mod models;
+mod render;
 
-use crate::models::{Position, SortBy};
+use chrono_tz::Tz;
 use clap::{Parser, Subcommand};
+use fplr::api::{self, FplClient, SnapshotOptions};
+use fplr::cache::Cache;
+use fplr::error::FplError;
+use fplr::filter::Filter;
+use fplr::models::{EventSelector, HistoryStat, PlayerStat, PlayerStatus, Position, SortBy};
+
+use crate::chart::GraphMetric;
+use crate::commands::{
+    CalendarOptions, Context, DgwOptions, FixtureOptions, PlayerOptions, PlayerSummaryOptions,
+    TickerSort,
+};
+use crate::render::{
+    OutputFormat, TimeFormat, parse_date_format, parse_time_zone, system_time_zone,
+};
+use std::io;
+use std::path::PathBuf;
+use std::process;
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 struct Args {
+    /// Base URL of the FPL API
+    #[arg(long, global = true, env = "FPLR_API_BASE", default_value = api::DEFAULT_API_BASE)]
+    api_base: String,
+    /// Bypass the response cache entirely
+    #[arg(long, global = true, conflicts_with = "refresh")]
+    no_cache: bool,
+    /// Ignore cached responses and fetch fresh data
+    #[arg(long, global = true)]
+    refresh: bool,
+    /// Output format
+    #[arg(short, long, global = true, value_enum, default_value_t = OutputFormat::Table)]
+    output: OutputFormat,
+    /// IANA time zone to show times in, e.g. Europe/London (default: system zone)
+    #[arg(long, global = true, env = "FPLR_TZ", value_name = "ZONE", value_parser = parse_time_zone)]
+    tz: Option<Tz>,
+    /// strftime format for times, e.g. "%a %d %b %H:%M"
+    #[arg(long, global = true, env = "FPLR_DATE_FORMAT", value_name = "FORMAT", value_parser = parse_date_format, default_value = TimeFormat::DEFAULT_FORMAT)]
+    date_format: String,
+    /// Read all data from a snapshot directory instead of the network
+    #[arg(long, global = true, value_name = "DIR")]
+    offline: Option<PathBuf>,
     #[command(subcommand)]
     commands: Commands,
 }
 
 #[derive(Subcommand, Debug)]
 enum Commands {
+    /// Inspect or clear the response cache
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
+    /// Export gameweek deadlines and fixtures as an iCalendar file
+    Calendar {
+        /// File to write (default: stdout)
+        #[arg(long, value_name = "FILE")]
+        out: Option<PathBuf>,
+        /// Minutes before each deadline to show a reminder (0 for none)
+        #[arg(long, value_name = "MINUTES", default_value = "90")]
+        alarm: u32,
+        /// Also include the fixtures of teams matching these names
+        #[arg(short, long, value_delimiter = ',')]
+        team: Vec<String>,
+    },
+    /// Compare players side by side, highlighting the best value in each row
+    Compare {
+        /// Player IDs or names
+        #[arg(num_args = 2.., required = true)]
+        players: Vec<String>,
+        /// Number of recent gameweeks to total points over
+        #[arg(long, default_value = "5")]
+        last: u64,
+    },
+    /// List blank and double gameweeks and unscheduled fixtures per team
+    Dgw {
+        /// First gameweek to scan (default: next gameweek)
+        #[arg(long)]
+        from: Option<u64>,
+        /// Last gameweek to scan (default: last gameweek of the season)
+        #[arg(long)]
+        to: Option<u64>,
+        /// Manager ID (entry ID) whose squad players to show
+        #[arg(short, long)]
+        manager: Option<u64>,
+    },
     /// Show dream team
-    DreamTeam { event_id: u32 },
-    /// Show upcoming fixtures
-    Fixture {},
+    #[command(allow_negative_numbers = true)]
+    DreamTeam {
+        /// Gameweek: an ID, current, next, previous, +N or -N (default: current)
+        event: Option<EventSelector>,
+    },
+    /// Show fixtures, by default the upcoming gameweek's
+    Fixture {
+        /// Show all fixtures of this gameweek
+        #[arg(short, long, conflicts_with_all = ["from", "to"])]
+        event: Option<u64>,
+        /// First gameweek of a range
+        #[arg(long)]
+        from: Option<u64>,
+        /// Last gameweek of a range
+        #[arg(long)]
+        to: Option<u64>,
+        /// Only fixtures of teams matching this name
+        #[arg(short, long)]
+        team: Option<String>,
+        /// Only finished fixtures with their scores (default: current gameweek)
+        #[arg(short, long)]
+        results: bool,
+        /// Only postponed fixtures that have not been given a gameweek yet
+        #[arg(short, long, conflicts_with_all = ["event", "from", "to", "results"])]
+        unscheduled: bool,
+    },
     /// Show gameweeks
     Gameweek {},
-    /// Show live player stats for a specific event
+    /// Show live player stats for a gameweek
+    #[command(allow_negative_numbers = true)]
     Live {
-        event: u32,
+        /// Gameweek: an ID, current, next, previous, +N or -N (default: current)
+        event: Option<EventSelector>,
         #[arg(short, long, default_value = "20")]
         limit: usize,
+        /// Sort keys, later ones breaking ties, e.g. points,bps
+        #[arg(short, long, value_delimiter = ',', default_value = "points")]
+        sort: Vec<SortBy>,
+        /// Sort lowest first
+        #[arg(long)]
+        asc: bool,
     },
     /// Show players
     Player {
-        #[arg(short, long, default_value = "points")]
-        sort: SortBy,
+        /// Sort keys, later ones breaking ties, e.g. form,points
+        #[arg(short, long, value_delimiter = ',', default_value = "points")]
+        sort: Vec<SortBy>,
+        /// Sort lowest first
+        #[arg(long)]
+        asc: bool,
         #[arg(short, long)]
         position: Option<Position>,
         #[arg(short, long, default_value = "20")]
         limit: usize,
         #[arg(short, long)]
         team: Option<String>,
+        /// Only show players with these availability statuses
+        #[arg(long, value_delimiter = ',')]
+        status: Vec<PlayerStatus>,
+        /// Extra stat columns to show, e.g. xg,xa,ict
+        #[arg(short, long, value_delimiter = ',')]
+        columns: Vec<PlayerStat>,
+        /// Only show players matching an expression, e.g. "cost <= 6.5 && form >= 5"
+        #[arg(short, long = "where", value_name = "EXPR", value_parser = Filter::parse)]
+        where_: Option<Filter>,
     },
-    /// Show a manager's team picks for a specific event
+    /// Show a manager's team picks for a gameweek
+    #[command(allow_negative_numbers = true)]
     Pick {
         /// Manager ID (entry ID)
         manager_id: u64,
-        /// Event ID
-        event_id: u32,
+        /// Gameweek: an ID, current, next, previous, +N or -N (default: current)
+        event: Option<EventSelector>,
     },
     /// Show player summary
     #[command(name = "player-summary")]
     PlayerSummary {
-        player_id: u64,
-        #[arg(short, long)]
-        graph: bool,
+        /// Player ID or name
+        player: String,
+        /// Extra stat columns for the gameweek history, e.g. bonus,bps,xg
+        #[arg(short, long, value_delimiter = ',')]
+        columns: Vec<HistoryStat>,
+        /// Chart metrics instead of the table, e.g. points,xg (default: points);
+        /// other output formats get the charted values per gameweek
+        #[arg(short, long, value_delimiter = ',', num_args = 0..=1, default_missing_value = "points")]
+        graph: Option<Vec<GraphMetric>>,
+        /// Overlay an N-gameweek rolling average on the charts
+        #[arg(long, value_name = "N", requires = "graph")]
+        rolling: Option<usize>,
+        /// Also show the remaining fixtures with their difficulty
+        #[arg(short, long, conflicts_with = "graph")]
+        fixtures: bool,
+        /// Also show totals of previous seasons
+        #[arg(short, long, conflicts_with = "graph")]
+        past: bool,
+    },
+    /// Search players by name, ignoring accents and small typos
+    Search {
+        query: String,
+        #[arg(short, long, default_value = "10")]
+        limit: usize,
+    },
+    /// Save API data for offline use
+    Snapshot {
+        #[command(subcommand)]
+        action: SnapshotAction,
     },
     /// Show teams
     Team {},
+    /// Show each team's upcoming fixtures coloured by difficulty
+    Ticker {
+        /// Number of upcoming gameweeks
+        #[arg(short, long, default_value = "6")]
+        weeks: u64,
+        #[arg(short, long, value_enum, default_value_t = TickerSort::Team)]
+        sort: TickerSort,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum CacheAction {
+    /// Show cached responses and their freshness
+    Status {},
+    /// Remove all cached responses
+    Clear {},
+}
+
+#[derive(Subcommand, Debug)]
+enum SnapshotAction {
+    /// Save bootstrap-static, fixtures and every started gameweek's live data and dream team
+    Save {
+        dir: PathBuf,
+        /// Also save every player's summary (one request per player)
+        #[arg(long)]
+        players: bool,
+        /// Also save this manager's picks for every started gameweek
+        #[arg(short, long = "manager", value_name = "ID", value_delimiter = ',')]
+        managers: Vec<u64>,
+    },
 }
 
 #[tokio::main]
 async fn main() {
     let args = Args::parse();
+    let cache_dir = Cache::default_dir();
+    let mut client = FplClient::new(&args.api_base);
+    if let Some(dir) = cache_dir.clone().filter(|_| !args.no_cache) {
+        let cache = Cache::new(dir);
+        client = client.with_cache(if args.refresh {
+            cache.refreshing()
+        } else {
+            cache
+        });
+    }
+    if let Some(dir) = args.offline {
+        client = client.with_offline(dir);
+    }
+    let ctx = Context {
+        client,
+        output: args.output,
+        time: TimeFormat {
+            tz: args.tz.unwrap_or_else(system_time_zone),
+            format: args.date_format,
+        },
+    };
 
-    match args.commands {
-        Commands::DreamTeam { event_id } => commands::handle_dream_team(event_id).await,
-        Commands::Gameweek {} => commands::handle_gameweek().await,
-        Commands::Live { event, limit } => commands::handle_live(event, limit).await,
+    let result = match args.commands {
+        Commands::Cache { action } => match cache_dir {
+            Some(dir) => match action {
+                CacheAction::Status {} => commands::handle_cache_status(&Cache::new(dir)),
+                CacheAction::Clear {} => commands::handle_cache_clear(&Cache::new(dir)),
+            },
+            None => Err(FplError::Io(io::Error::new(
+                io::ErrorKind::NotFound,
+                "could not determine the cache directory",
+            ))),
+        },
+        Commands::Calendar { out, alarm, team } => {
+            let options = CalendarOptions {
+                out,
+                alarm,
+                teams: team,
+            };
+            commands::handle_calendar(&ctx, options).await
+        }
+        Commands::Compare { players, last } => commands::handle_compare(&ctx, &players, last).await,
+        Commands::Dgw { from, to, manager } => {
+            let options = DgwOptions { from, to, manager };
+            commands::handle_dgw(&ctx, options).await
+        }
+        Commands::DreamTeam { event } => {
+            commands::handle_dream_team(&ctx, event.unwrap_or_default()).await
+        }
+        Commands::Gameweek {} => commands::handle_gameweek(&ctx).await,
+        Commands::Live {
+            event,
+            limit,
+            sort,
+            asc,
+        } => commands::handle_live(&ctx, event.unwrap_or_default(), limit, sort, asc).await,
         Commands::Player {
             sort,
+            asc,
             position,
             limit,
             team,
-        } => commands::handle_player(sort, position, limit, team).await,
-        Commands::Pick {
-            manager_id,
-            event_id,
-        } => commands::handle_pick(manager_id, event_id).await,
-        Commands::PlayerSummary { player_id, graph } => {
-            commands::handle_player_summary(player_id, graph).await
+            status,
+            columns,
+            where_,
+        } => {
+            let options = PlayerOptions {
+                sort,
+                ascending: asc,
+                position,
+                limit,
+                team,
+                status,
+                columns,
+                filter: where_,
+            };
+            commands::handle_player(&ctx, options).await
+        }
+        Commands::Pick { manager_id, event } => {
+            commands::handle_pick(&ctx, manager_id, event.unwrap_or_default()).await
+        }
+        Commands::PlayerSummary {
+            player,
+            columns,
+            graph,
+            rolling,
+            fixtures,
+            past,
+        } => {
+            let options = PlayerSummaryOptions {
+                columns,
+                graph: graph.unwrap_or_default(),
+                rolling,
+                fixtures,
+                past,
+            };
+            commands::handle_player_summary(&ctx, &player, options).await
         }
-        Commands::Team {} => commands::handle_team().await,
-        Commands::Fixture {} => commands::handle_fixture().await,
+        Commands::Search { query, limit } => commands::handle_search(&ctx, &query, limit).await,
+        Commands::Snapshot {
+            action:
+                SnapshotAction::Save {
+                    dir,
+                    players,
+                    managers,
+                },
+        } => {
+            let options = SnapshotOptions { players, managers };
+            commands::handle_snapshot_save(&ctx, &dir, &options).await
+        }
+        Commands::Team {} => commands::handle_team(&ctx).await,
+        Commands::Ticker { weeks, sort } => commands::handle_ticker(&ctx, weeks, sort).await,
+        Commands::Fixture {
+            event,
+            from,
+            to,
+            team,
+            results,
+            unscheduled,
+        } => {
+            let options = FixtureOptions {
+                event,
+                from,
+                to,
+                team,
+                results,
+                unscheduled,
+            };
+            commands::handle_fixture(&ctx, options).await
+        }
+    };
+
+    if let Err(e) = result {
+        eprintln!("Error: {}", e);
+        process::exit(e.exit_code());
     }
 }
@@ -1,13 +1,436 @@
+use std::fmt;
+use std::str::FromStr;
+
 use clap::ValueEnum;
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, Default, ValueEnum)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
 pub enum SortBy {
     Cost,
     SelectedBy,
     Form,
     #[default]
     Points,
+    Minutes,
+    Goals,
+    Assists,
+    Bonus,
+    Bps,
+    Xg,
+    Xa,
+    Xgi,
+    Ict,
+    PointsPerGame,
+    PointsPerMillion,
+    TransfersInEvent,
+    PriceChange,
+}
+
+impl SortBy {
+    /// The season value players are ranked by.
+    pub fn value(&self, element: &Element) -> f64 {
+        let stat = match self {
+            SortBy::Cost => return element.now_cost as f64 / 10.0,
+            SortBy::SelectedBy => return parse_decimal(&element.selected_by_percent),
+            SortBy::Form => return parse_decimal(&element.form),
+            SortBy::Points => return element.total_points as f64,
+            SortBy::PointsPerMillion => {
+                return points_per_million(element.total_points, element.now_cost);
+            }
+            SortBy::Minutes => PlayerStat::Minutes,
+            SortBy::Goals => PlayerStat::Goals,
+            SortBy::Assists => PlayerStat::Assists,
+            SortBy::Bonus => PlayerStat::Bonus,
+            SortBy::Bps => PlayerStat::Bps,
+            SortBy::Xg => PlayerStat::Xg,
+            SortBy::Xa => PlayerStat::Xa,
+            SortBy::Xgi => PlayerStat::Xgi,
+            SortBy::Ict => PlayerStat::Ict,
+            SortBy::PointsPerGame => PlayerStat::PointsPerGame,
+            SortBy::TransfersInEvent => PlayerStat::TransfersInEvent,
+            SortBy::PriceChange => PlayerStat::PriceChange,
+        };
+        stat.value(element).unwrap_or(0.0)
+    }
+
+    /// The value live gameweek rows are ranked by. Match stats come from the
+    /// gameweek itself; price, ownership and form from the player's season
+    /// data, when known.
+    pub fn live_value(&self, stats: &LiveStats, element: Option<&Element>) -> f64 {
+        match self {
+            SortBy::Points => stats.total_points as f64,
+            SortBy::Minutes => stats.minutes as f64,
+            SortBy::Goals => stats.goals_scored as f64,
+            SortBy::Assists => stats.assists as f64,
+            SortBy::Bonus => stats.bonus as f64,
+            SortBy::Bps => stats.bps as f64,
+            SortBy::Xg => parse_decimal(&stats.expected_goals),
+            SortBy::Xa => parse_decimal(&stats.expected_assists),
+            SortBy::Xgi => parse_decimal(&stats.expected_goal_involvements),
+            SortBy::Ict => parse_decimal(&stats.ict_index),
+            SortBy::PointsPerMillion => element.map_or(0.0, |element| {
+                points_per_million(stats.total_points, element.now_cost)
+            }),
+            SortBy::Cost
+            | SortBy::SelectedBy
+            | SortBy::Form
+            | SortBy::PointsPerGame
+            | SortBy::TransfersInEvent
+            | SortBy::PriceChange => element.map_or(0.0, |element| self.value(element)),
+        }
+    }
+}
+
+fn points_per_million(points: i64, now_cost: u64) -> f64 {
+    if now_cost == 0 {
+        0.0
+    } else {
+        points as f64 / (now_cost as f64 / 10.0)
+    }
+}
+
+/// Optional numeric columns of the `player` table.
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum PlayerStat {
+    Minutes,
+    Starts,
+    Goals,
+    Assists,
+    CleanSheets,
+    GoalsConceded,
+    Saves,
+    Bonus,
+    Bps,
+    Xg,
+    Xa,
+    Xgi,
+    Xgc,
+    Xg90,
+    Xa90,
+    Influence,
+    Creativity,
+    Threat,
+    Ict,
+    DefensiveContribution,
+    PointsPerGame,
+    EventPoints,
+    ExpectedPoints,
+    Chance,
+    TransfersIn,
+    TransfersOut,
+    TransfersInEvent,
+    TransfersOutEvent,
+    PriceChange,
+    PriceChangeStart,
+}
+
+impl PlayerStat {
+    pub fn key(&self) -> &str {
+        match self {
+            PlayerStat::Minutes => "minutes",
+            PlayerStat::Starts => "starts",
+            PlayerStat::Goals => "goals_scored",
+            PlayerStat::Assists => "assists",
+            PlayerStat::CleanSheets => "clean_sheets",
+            PlayerStat::GoalsConceded => "goals_conceded",
+            PlayerStat::Saves => "saves",
+            PlayerStat::Bonus => "bonus",
+            PlayerStat::Bps => "bps",
+            PlayerStat::Xg => "expected_goals",
+            PlayerStat::Xa => "expected_assists",
+            PlayerStat::Xgi => "expected_goal_involvements",
+            PlayerStat::Xgc => "expected_goals_conceded",
+            PlayerStat::Xg90 => "expected_goals_per_90",
+            PlayerStat::Xa90 => "expected_assists_per_90",
+            PlayerStat::Influence => "influence",
+            PlayerStat::Creativity => "creativity",
+            PlayerStat::Threat => "threat",
+            PlayerStat::Ict => "ict_index",
+            PlayerStat::DefensiveContribution => "defensive_contribution",
+            PlayerStat::PointsPerGame => "points_per_game",
+            PlayerStat::EventPoints => "event_points",
+            PlayerStat::ExpectedPoints => "ep_next",
+            PlayerStat::Chance => "chance_of_playing_next_round",
+            PlayerStat::TransfersIn => "transfers_in",
+            PlayerStat::TransfersOut => "transfers_out",
+            PlayerStat::TransfersInEvent => "transfers_in_event",
+            PlayerStat::TransfersOutEvent => "transfers_out_event",
+            PlayerStat::PriceChange => "cost_change_event",
+            PlayerStat::PriceChangeStart => "cost_change_start",
+        }
+    }
+
+    pub fn title(&self) -> &str {
+        match self {
+            PlayerStat::Minutes => "Min",
+            PlayerStat::Starts => "Starts",
+            PlayerStat::Goals => "G",
+            PlayerStat::Assists => "A",
+            PlayerStat::CleanSheets => "CS",
+            PlayerStat::GoalsConceded => "GC",
+            PlayerStat::Saves => "S",
+            PlayerStat::Bonus => "B",
+            PlayerStat::Bps => "BPS",
+            PlayerStat::Xg => "xG",
+            PlayerStat::Xa => "xA",
+            PlayerStat::Xgi => "xGI",
+            PlayerStat::Xgc => "xGC",
+            PlayerStat::Xg90 => "xG/90",
+            PlayerStat::Xa90 => "xA/90",
+            PlayerStat::Influence => "Inf",
+            PlayerStat::Creativity => "Cre",
+            PlayerStat::Threat => "Thr",
+            PlayerStat::Ict => "ICT",
+            PlayerStat::DefensiveContribution => "DC",
+            PlayerStat::PointsPerGame => "PPG",
+            PlayerStat::EventPoints => "GW Pts",
+            PlayerStat::ExpectedPoints => "xPts",
+            PlayerStat::Chance => "Chance",
+            PlayerStat::TransfersIn => "In",
+            PlayerStat::TransfersOut => "Out",
+            PlayerStat::TransfersInEvent => "GW In",
+            PlayerStat::TransfersOutEvent => "GW Out",
+            PlayerStat::PriceChange => "GW +/-",
+            PlayerStat::PriceChangeStart => "+/-",
+        }
+    }
+
+    /// Whether the stat is a count rather than a decimal.
+    pub fn is_integer(&self) -> bool {
+        !matches!(
+            self,
+            PlayerStat::Xg
+                | PlayerStat::Xa
+                | PlayerStat::Xgi
+                | PlayerStat::Xgc
+                | PlayerStat::Xg90
+                | PlayerStat::Xa90
+                | PlayerStat::Influence
+                | PlayerStat::Creativity
+                | PlayerStat::Threat
+                | PlayerStat::Ict
+                | PlayerStat::PointsPerGame
+                | PlayerStat::ExpectedPoints
+                | PlayerStat::PriceChange
+                | PlayerStat::PriceChangeStart
+        )
+    }
+
+    /// The stat for `element`; prices are in millions. `None` when the API
+    /// has no value, e.g. no availability news for `Chance`.
+    pub fn value(&self, element: &Element) -> Option<f64> {
+        let value = match self {
+            PlayerStat::Minutes => element.minutes as f64,
+            PlayerStat::Starts => element.starts as f64,
+            PlayerStat::Goals => element.goals_scored as f64,
+            PlayerStat::Assists => element.assists as f64,
+            PlayerStat::CleanSheets => element.clean_sheets as f64,
+            PlayerStat::GoalsConceded => element.goals_conceded as f64,
+            PlayerStat::Saves => element.saves as f64,
+            PlayerStat::Bonus => element.bonus as f64,
+            PlayerStat::Bps => element.bps as f64,
+            PlayerStat::Xg => parse_decimal(&element.expected_goals),
+            PlayerStat::Xa => parse_decimal(&element.expected_assists),
+            PlayerStat::Xgi => parse_decimal(&element.expected_goal_involvements),
+            PlayerStat::Xgc => parse_decimal(&element.expected_goals_conceded),
+            PlayerStat::Xg90 => element.expected_goals_per_90,
+            PlayerStat::Xa90 => element.expected_assists_per_90,
+            PlayerStat::Influence => parse_decimal(&element.influence),
+            PlayerStat::Creativity => parse_decimal(&element.creativity),
+            PlayerStat::Threat => parse_decimal(&element.threat),
+            PlayerStat::Ict => parse_decimal(&element.ict_index),
+            PlayerStat::DefensiveContribution => element.defensive_contribution as f64,
+            PlayerStat::PointsPerGame => parse_decimal(&element.points_per_game),
+            PlayerStat::EventPoints => element.event_points as f64,
+            PlayerStat::ExpectedPoints => parse_decimal(element.ep_next.as_deref()?),
+            PlayerStat::Chance => element.chance_of_playing_next_round? as f64,
+            PlayerStat::TransfersIn => element.transfers_in as f64,
+            PlayerStat::TransfersOut => element.transfers_out as f64,
+            PlayerStat::TransfersInEvent => element.transfers_in_event as f64,
+            PlayerStat::TransfersOutEvent => element.transfers_out_event as f64,
+            PlayerStat::PriceChange => element.cost_change_event as f64 / 10.0,
+            PlayerStat::PriceChangeStart => element.cost_change_start as f64 / 10.0,
+        };
+        Some(value)
+    }
+}
+
+/// Optional columns of the `player-summary` history table.
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum HistoryStat {
+    Starts,
+    CleanSheets,
+    GoalsConceded,
+    OwnGoals,
+    PenaltiesSaved,
+    PenaltiesMissed,
+    YellowCards,
+    RedCards,
+    Saves,
+    Bonus,
+    Bps,
+    Xg,
+    Xa,
+    Xgi,
+    Xgc,
+    Influence,
+    Creativity,
+    Threat,
+    Ict,
+    DefensiveContribution,
+    Cbi,
+    Recoveries,
+    Tackles,
+    Price,
+    Selected,
+    TransfersBalance,
+}
+
+impl HistoryStat {
+    pub fn key(&self) -> &str {
+        match self {
+            HistoryStat::Starts => "starts",
+            HistoryStat::CleanSheets => "clean_sheets",
+            HistoryStat::GoalsConceded => "goals_conceded",
+            HistoryStat::OwnGoals => "own_goals",
+            HistoryStat::PenaltiesSaved => "penalties_saved",
+            HistoryStat::PenaltiesMissed => "penalties_missed",
+            HistoryStat::YellowCards => "yellow_cards",
+            HistoryStat::RedCards => "red_cards",
+            HistoryStat::Saves => "saves",
+            HistoryStat::Bonus => "bonus",
+            HistoryStat::Bps => "bps",
+            HistoryStat::Xg => "expected_goals",
+            HistoryStat::Xa => "expected_assists",
+            HistoryStat::Xgi => "expected_goal_involvements",
+            HistoryStat::Xgc => "expected_goals_conceded",
+            HistoryStat::Influence => "influence",
+            HistoryStat::Creativity => "creativity",
+            HistoryStat::Threat => "threat",
+            HistoryStat::Ict => "ict_index",
+            HistoryStat::DefensiveContribution => "defensive_contribution",
+            HistoryStat::Cbi => "clearances_blocks_interceptions",
+            HistoryStat::Recoveries => "recoveries",
+            HistoryStat::Tackles => "tackles",
+            HistoryStat::Price => "value",
+            HistoryStat::Selected => "selected",
+            HistoryStat::TransfersBalance => "transfers_balance",
+        }
+    }
+
+    pub fn title(&self) -> &str {
+        match self {
+            HistoryStat::Starts => "Starts",
+            HistoryStat::CleanSheets => "CS",
+            HistoryStat::GoalsConceded => "GC",
+            HistoryStat::OwnGoals => "OG",
+            HistoryStat::PenaltiesSaved => "PS",
+            HistoryStat::PenaltiesMissed => "PM",
+            HistoryStat::YellowCards => "YC",
+            HistoryStat::RedCards => "RC",
+            HistoryStat::Saves => "S",
+            HistoryStat::Bonus => "B",
+            HistoryStat::Bps => "BPS",
+            HistoryStat::Xg => "xG",
+            HistoryStat::Xa => "xA",
+            HistoryStat::Xgi => "xGI",
+            HistoryStat::Xgc => "xGC",
+            HistoryStat::Influence => "Inf",
+            HistoryStat::Creativity => "Cre",
+            HistoryStat::Threat => "Thr",
+            HistoryStat::Ict => "ICT",
+            HistoryStat::DefensiveContribution => "DC",
+            HistoryStat::Cbi => "CBI",
+            HistoryStat::Recoveries => "Rec",
+            HistoryStat::Tackles => "Tkl",
+            HistoryStat::Price => "Price",
+            HistoryStat::Selected => "Selected",
+            HistoryStat::TransfersBalance => "Net Tr",
+        }
+    }
+
+    /// Whether the stat is a count rather than a decimal.
+    pub fn is_integer(&self) -> bool {
+        !matches!(
+            self,
+            HistoryStat::Xg
+                | HistoryStat::Xa
+                | HistoryStat::Xgi
+                | HistoryStat::Xgc
+                | HistoryStat::Influence
+                | HistoryStat::Creativity
+                | HistoryStat::Threat
+                | HistoryStat::Ict
+                | HistoryStat::Price
+        )
+    }
+
+    /// Whether summing the stat over gameweeks gives a season total.
+    /// Snapshots such as price and ownership do not add up.
+    pub fn is_additive(&self) -> bool {
+        !matches!(self, HistoryStat::Price | HistoryStat::Selected)
+    }
+
+    /// The stat for one gameweek; prices are in millions.
+    pub fn value(&self, history: &PlayerHistory) -> f64 {
+        match self {
+            HistoryStat::Starts => history.starts as f64,
+            HistoryStat::CleanSheets => history.clean_sheets as f64,
+            HistoryStat::GoalsConceded => history.goals_conceded as f64,
+            HistoryStat::OwnGoals => history.own_goals as f64,
+            HistoryStat::PenaltiesSaved => history.penalties_saved as f64,
+            HistoryStat::PenaltiesMissed => history.penalties_missed as f64,
+            HistoryStat::YellowCards => history.yellow_cards as f64,
+            HistoryStat::RedCards => history.red_cards as f64,
+            HistoryStat::Saves => history.saves as f64,
+            HistoryStat::Bonus => history.bonus as f64,
+            HistoryStat::Bps => history.bps as f64,
+            HistoryStat::Xg => parse_decimal(&history.expected_goals),
+            HistoryStat::Xa => parse_decimal(&history.expected_assists),
+            HistoryStat::Xgi => parse_decimal(&history.expected_goal_involvements),
+            HistoryStat::Xgc => parse_decimal(&history.expected_goals_conceded),
+            HistoryStat::Influence => parse_decimal(&history.influence),
+            HistoryStat::Creativity => parse_decimal(&history.creativity),
+            HistoryStat::Threat => parse_decimal(&history.threat),
+            HistoryStat::Ict => parse_decimal(&history.ict_index),
+            HistoryStat::DefensiveContribution => history.defensive_contribution as f64,
+            HistoryStat::Cbi => history.clearances_blocks_interceptions as f64,
+            HistoryStat::Recoveries => history.recoveries as f64,
+            HistoryStat::Tackles => history.tackles as f64,
+            HistoryStat::Price => history.value as f64 / 10.0,
+            HistoryStat::Selected => history.selected as f64,
+            HistoryStat::TransfersBalance => history.transfers_balance as f64,
+        }
+    }
+}
+
+/// Availability flag of a player (`Element.status`).
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum PlayerStatus {
+    Available,
+    Doubtful,
+    Injured,
+    Suspended,
+    Unavailable,
+    NotInSquad,
+}
+
+impl PlayerStatus {
+    pub fn code(&self) -> &str {
+        match self {
+            PlayerStatus::Available => "a",
+            PlayerStatus::Doubtful => "d",
+            PlayerStatus::Injured => "i",
+            PlayerStatus::Suspended => "s",
+            PlayerStatus::Unavailable => "u",
+            PlayerStatus::NotInSquad => "n",
+        }
+    }
+}
+
+/// Parses the decimal strings the API uses for form, xG, ICT and friends.
+pub fn parse_decimal(s: &str) -> f64 {
+    s.parse().unwrap_or(0.0)
 }
 
 #[derive(Clone, Debug, ValueEnum)]
@@ -27,7 +450,7 @@ impl Position {
             Position::Forward => 4,
         }
     }
-    pub fn display_name(&self) -> &str {
+    pub fn display_name(&self) -> &'static str {
         match self {
             Position::Goalkeeper => "GKP",
             Position::Defender => "DEF",
@@ -75,17 +498,151 @@ pub struct Event {
     pub finished: bool,
 }
 
+/// A gameweek given by ID or relative to the current one, as accepted on
+/// the command line: `5`, `current`, `next`, `previous` (or `last`), `+2`
+/// or `-1`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum EventSelector {
+    Id(u64),
+    #[default]
+    Current,
+    Next,
+    /// An offset from the current gameweek; `previous` is `-1`.
+    Offset(i64),
+}
+
+impl FromStr for EventSelector {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        match s.to_lowercase().as_str() {
+            "current" => return Ok(EventSelector::Current),
+            "next" => return Ok(EventSelector::Next),
+            "previous" | "prev" | "last" => return Ok(EventSelector::Offset(-1)),
+            _ => {}
+        }
+        if s.starts_with(['+', '-']) {
+            s.parse()
+                .map(EventSelector::Offset)
+                .map_err(|_| format!("invalid gameweek offset `{}`", s))
+        } else {
+            s.parse().map(EventSelector::Id).map_err(|_| {
+                format!(
+                    "invalid gameweek `{}`; use a number, current, next, previous, +N or -N",
+                    s
+                )
+            })
+        }
+    }
+}
+
+impl fmt::Display for EventSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EventSelector::Id(id) => write!(f, "{}", id),
+            EventSelector::Current => write!(f, "current"),
+            EventSelector::Next => write!(f, "next"),
+            EventSelector::Offset(offset) => write!(f, "{:+}", offset),
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Element {
     pub id: u64,
+    pub code: u64,
     pub web_name: String,
+    pub first_name: String,
+    pub second_name: String,
     pub element_type: u64,
     pub team: u64,
+    pub team_code: u64,
+    pub photo: String,
+    pub squad_number: Option<u32>,
+    pub status: String,
+    pub news: String,
+    pub news_added: Option<String>,
+    pub chance_of_playing_next_round: Option<u8>,
+    pub chance_of_playing_this_round: Option<u8>,
+    pub can_select: bool,
+    pub can_transact: bool,
+    pub removed: bool,
+    pub special: bool,
+    pub in_dreamteam: bool,
+    pub dreamteam_count: u32,
     pub now_cost: u64,
+    pub cost_change_event: i64,
+    pub cost_change_event_fall: i64,
+    pub cost_change_start: i64,
+    pub cost_change_start_fall: i64,
     pub selected_by_percent: String,
+    pub transfers_in: u64,
+    pub transfers_in_event: u64,
+    pub transfers_out: u64,
+    pub transfers_out_event: u64,
     pub form: String,
+    pub points_per_game: String,
+    pub ep_next: Option<String>,
+    pub ep_this: Option<String>,
+    pub event_points: i64,
     pub total_points: i64,
-    pub news: String,
+    pub value_form: String,
+    pub value_season: String,
+    pub minutes: u64,
+    pub starts: u64,
+    pub goals_scored: u64,
+    pub assists: u64,
+    pub clean_sheets: u64,
+    pub goals_conceded: u64,
+    pub own_goals: u64,
+    pub penalties_saved: u64,
+    pub penalties_missed: u64,
+    pub yellow_cards: u64,
+    pub red_cards: u64,
+    pub saves: u64,
+    pub bonus: u64,
+    pub bps: i64,
+    pub influence: String,
+    pub creativity: String,
+    pub threat: String,
+    pub ict_index: String,
+    pub clearances_blocks_interceptions: u64,
+    pub recoveries: u64,
+    pub tackles: u64,
+    pub defensive_contribution: u64,
+    pub expected_goals: String,
+    pub expected_assists: String,
+    pub expected_goal_involvements: String,
+    pub expected_goals_conceded: String,
+    pub expected_goals_per_90: f64,
+    pub expected_assists_per_90: f64,
+    pub expected_goal_involvements_per_90: f64,
+    pub expected_goals_conceded_per_90: f64,
+    pub saves_per_90: f64,
+    pub goals_conceded_per_90: f64,
+    pub starts_per_90: f64,
+    pub clean_sheets_per_90: f64,
+    pub corners_and_indirect_freekicks_order: Option<u8>,
+    pub direct_freekicks_order: Option<u8>,
+    pub penalties_order: Option<u8>,
+    pub influence_rank: u32,
+    pub creativity_rank: u32,
+    pub threat_rank: u32,
+    pub ict_index_rank: u32,
+    pub now_cost_rank: u32,
+    pub form_rank: u32,
+    pub points_per_game_rank: u32,
+    pub selected_rank: u32,
+    // Added in recent seasons, so absent from older snapshots.
+    #[serde(default)]
+    pub defensive_contribution_per_90: f64,
+    #[serde(default)]
+    pub birth_date: Option<String>,
+    #[serde(default)]
+    pub team_join_date: Option<String>,
+    #[serde(default)]
+    pub region: Option<u64>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -109,6 +666,41 @@ pub struct Team {
     pub short_name: String,
 }
 
+#[derive(Debug, Deserialize, Serialize)]
+pub struct FixtureStatValue {
+    pub value: i64,
+    pub element: u64,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct FixtureStat {
+    pub identifier: String,
+    pub a: Vec<FixtureStatValue>,
+    pub h: Vec<FixtureStatValue>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Fixture {
+    pub id: u64,
+    pub code: u64,
+    /// `None` while the fixture is postponed or not yet scheduled.
+    pub event: Option<u64>,
+    pub kickoff_time: Option<String>,
+    pub team_h: u64,
+    pub team_a: u64,
+    pub team_h_score: Option<u64>,
+    pub team_a_score: Option<u64>,
+    pub team_h_difficulty: u8,
+    pub team_a_difficulty: u8,
+    #[serde(default)]
+    pub started: Option<bool>,
+    pub finished: bool,
+    pub finished_provisional: bool,
+    pub minutes: u64,
+    pub provisional_start_time: bool,
+    pub stats: Vec<FixtureStat>,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct BootstrapStatic {
     pub events: Vec<Event>,
@@ -161,9 +753,81 @@ pub struct PlayerHistory {
     pub transfers_out: u64,
 }
 
+/// One of the player's remaining fixtures, from their own team's view.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct PlayerFixture {
+    pub id: u64,
+    pub code: u64,
+    pub team_h: u64,
+    pub team_h_score: Option<u64>,
+    pub team_a: u64,
+    pub team_a_score: Option<u64>,
+    /// `None` while the fixture is postponed or not yet scheduled.
+    pub event: Option<u64>,
+    pub finished: bool,
+    pub minutes: u64,
+    pub provisional_start_time: bool,
+    pub kickoff_time: Option<String>,
+    pub event_name: Option<String>,
+    pub is_home: bool,
+    pub difficulty: u8,
+}
+
+impl PlayerFixture {
+    pub fn opponent(&self) -> u64 {
+        if self.is_home {
+            self.team_a
+        } else {
+            self.team_h
+        }
+    }
+}
+
+/// Season totals for one of the player's previous Premier League seasons.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct PlayerSeason {
+    pub season_name: String,
+    pub element_code: u64,
+    pub start_cost: u64,
+    pub end_cost: u64,
+    pub total_points: i64,
+    pub minutes: u64,
+    pub goals_scored: u64,
+    pub assists: u64,
+    pub clean_sheets: u64,
+    pub goals_conceded: u64,
+    pub own_goals: u64,
+    pub penalties_saved: u64,
+    pub penalties_missed: u64,
+    pub yellow_cards: u64,
+    pub red_cards: u64,
+    pub saves: u64,
+    pub bonus: u64,
+    pub bps: i64,
+    pub influence: String,
+    pub creativity: String,
+    pub threat: String,
+    pub ict_index: String,
+    #[serde(default)]
+    pub clearances_blocks_interceptions: u64,
+    #[serde(default)]
+    pub recoveries: u64,
+    #[serde(default)]
+    pub tackles: u64,
+    #[serde(default)]
+    pub defensive_contribution: u64,
+    pub starts: u64,
+    pub expected_goals: String,
+    pub expected_assists: String,
+    pub expected_goal_involvements: String,
+    pub expected_goals_conceded: String,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct PlayerSummary {
+    pub fixtures: Vec<PlayerFixture>,
     pub history: Vec<PlayerHistory>,
+    pub history_past: Vec<PlayerSeason>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -265,4 +929,49 @@ mod tests {
         assert_eq!(Position::Midfielder.element_type_id(), 3);
         assert_eq!(Position::Forward.element_type_id(), 4);
     }
+
+    #[test]
+    fn test_fixture_allows_unscheduled() {
+        let fixture: Fixture = serde_json::from_str(
+            r#"{"id": 99, "code": 2561999, "event": null, "kickoff_time": null,
+                "team_h": 2, "team_a": 4, "team_h_score": null, "team_a_score": null,
+                "team_h_difficulty": 3, "team_a_difficulty": 2, "started": null,
+                "finished": false, "finished_provisional": false, "minutes": 0,
+                "provisional_start_time": false, "stats": []}"#,
+        )
+        .unwrap();
+        assert_eq!(fixture.event, None);
+        assert_eq!(fixture.kickoff_time, None);
+        assert_eq!(fixture.started, None);
+    }
+
+    #[test]
+    fn test_player_summary() {
+        let summary: PlayerSummary =
+            serde_json::from_str(include_str!("../tests/data/element-summary.json")).unwrap();
+        assert_eq!(summary.history.len(), 3);
+        assert_eq!(summary.fixtures[0].opponent(), 7);
+        assert_eq!(summary.fixtures[1].opponent(), 18);
+        assert_eq!(summary.fixtures[2].event, None);
+        assert_eq!(summary.history_past[1].season_name, "2024/25");
+        assert_eq!(summary.history_past[1].end_cost, 149);
+
+        let history = &summary.history[1];
+        assert_eq!(HistoryStat::Xgi.value(history), 1.53);
+        assert_eq!(HistoryStat::Price.value(history), 15.0);
+        assert!(!HistoryStat::Price.is_additive());
+    }
+
+    #[test]
+    fn test_player_stat_values() {
+        let element: Element =
+            serde_json::from_str(include_str!("../tests/data/element.json")).unwrap();
+        assert_eq!(PlayerStat::Xg.value(&element), Some(15.12));
+        assert_eq!(PlayerStat::Minutes.value(&element), Some(1313.0));
+        assert_eq!(PlayerStat::PriceChange.value(&element), Some(0.1));
+        assert_eq!(PlayerStat::Chance.value(&element), None);
+        assert_eq!(SortBy::SelectedBy.value(&element), 72.9);
+        assert_eq!(SortBy::Cost.value(&element), 15.0);
+        assert!((SortBy::PointsPerMillion.value(&element) - 122.0 / 15.0).abs() < 1e-9);
+    }
 }
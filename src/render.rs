@@ -0,0 +1,575 @@
+use std::collections::HashMap;
+use std::io::IsTerminal;
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use clap::ValueEnum;
+use serde_json::{Map, Value};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// Text columns are never shrunk below this width to fit the terminal.
+const MIN_COLUMN_WIDTH: usize = 6;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum OutputFormat {
+    #[default]
+    Table,
+    Json,
+    Csv,
+    Tsv,
+    Markdown,
+}
+
+/// How a cell stands out in terminal tables.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CellStyle {
+    Bold,
+    /// RGB text and background colours, filling the whole column width.
+    Colour {
+        fg: [u8; 3],
+        bg: [u8; 3],
+    },
+}
+
+struct Column {
+    key: String,
+    title: String,
+    max_width: Option<usize>,
+}
+
+/// Rows of structured cells that can be rendered in any [`OutputFormat`].
+///
+/// Each column has a machine-readable key, used for JSON objects, and a
+/// title used as the header of the other formats.
+pub struct Table {
+    columns: Vec<Column>,
+    rows: Vec<Vec<Value>>,
+    footer: Option<Vec<Value>>,
+    styles: HashMap<(usize, usize), CellStyle>,
+}
+
+impl Table {
+    pub fn new(columns: &[(&str, &str)]) -> Self {
+        Self {
+            columns: columns
+                .iter()
+                .map(|(key, title)| Column {
+                    key: key.to_string(),
+                    title: title.to_string(),
+                    max_width: None,
+                })
+                .collect(),
+            rows: Vec::new(),
+            footer: None,
+            styles: HashMap::new(),
+        }
+    }
+
+    /// Truncates the `key` column to `width` terminal cells in table output.
+    pub fn max_width(mut self, key: &str, width: usize) -> Self {
+        if let Some(column) = self.columns.iter_mut().find(|c| c.key == key) {
+            column.max_width = Some(width);
+        }
+        self
+    }
+
+    pub fn push(&mut self, row: Vec<Value>) {
+        debug_assert_eq!(row.len(), self.columns.len());
+        self.rows.push(row);
+    }
+
+    /// Sets a summary row such as season totals. It is shown below a rule in
+    /// table output and as a last row in Markdown, but left out of JSON, CSV
+    /// and TSV so that every record there is a data row.
+    pub fn footer(&mut self, row: Vec<Value>) {
+        debug_assert_eq!(row.len(), self.columns.len());
+        self.footer = Some(row);
+    }
+
+    /// Emphasises a cell: bold in terminal tables and `**` in Markdown.
+    pub fn highlight(&mut self, row: usize, column: usize) {
+        self.style(row, column, CellStyle::Bold);
+    }
+
+    /// Styles a cell of terminal tables; only bold carries over to Markdown.
+    pub fn style(&mut self, row: usize, column: usize, style: CellStyle) {
+        self.styles.insert((row, column), style);
+    }
+
+    pub fn print(&self, format: OutputFormat) {
+        print!("{}", self.render(format));
+    }
+
+    pub fn render(&self, format: OutputFormat) -> String {
+        match format {
+            OutputFormat::Table => self.render_table(terminal_width(), use_styles()),
+            OutputFormat::Json => self.render_json(),
+            OutputFormat::Csv => self.render_delimited(',', csv_escape),
+            OutputFormat::Tsv => self.render_delimited('\t', tsv_escape),
+            OutputFormat::Markdown => self.render_markdown(),
+        }
+    }
+
+    fn titles(&self) -> Vec<String> {
+        self.columns.iter().map(|c| c.title.clone()).collect()
+    }
+
+    fn text_rows(&self) -> Vec<Vec<String>> {
+        self.rows
+            .iter()
+            .map(|row| row.iter().map(cell_text).collect())
+            .collect()
+    }
+
+    /// Aligns columns by display width, truncating cells to each column's
+    /// limit and shrinking text columns until the table fits `max_total`.
+    /// Cell styles are applied with ANSI escapes when `styled` is set.
+    fn render_table(&self, max_total: Option<usize>, styled: bool) -> String {
+        let titles = self.titles();
+        let rows = self.text_rows();
+        let footer: Option<Vec<String>> = self
+            .footer
+            .as_ref()
+            .map(|row| row.iter().map(cell_text).collect());
+        let mut widths: Vec<usize> = self
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(i, column)| {
+                let width = rows
+                    .iter()
+                    .chain(footer.iter())
+                    .map(|row| row[i].width())
+                    .chain(std::iter::once(titles[i].width()))
+                    .max()
+                    .unwrap_or(0);
+                column.max_width.map_or(width, |max| width.min(max))
+            })
+            .collect();
+
+        if let Some(max_total) = max_total {
+            let shrinkable: Vec<bool> = (0..self.columns.len())
+                .map(|i| self.rows.iter().any(|row| row[i].is_string()))
+                .collect();
+            let separators = widths.len().saturating_sub(1);
+            while widths.iter().sum::<usize>() + separators > max_total {
+                let widest = (0..widths.len())
+                    .filter(|&i| shrinkable[i] && widths[i] > MIN_COLUMN_WIDTH)
+                    .max_by_key(|&i| widths[i]);
+                match widest {
+                    Some(i) => widths[i] -= 1,
+                    None => break,
+                }
+            }
+        }
+
+        // `row` is the index into `self.rows`, used to look up cell styles.
+        let line = |cells: &[String], row: Option<usize>| -> String {
+            let cells: Vec<String> = cells
+                .iter()
+                .zip(&widths)
+                .enumerate()
+                .map(|(c, (cell, &width))| {
+                    let text = truncate(cell, width);
+                    let padding = " ".repeat(width.saturating_sub(text.width()));
+                    let style = row.and_then(|r| self.styles.get(&(r, c)));
+                    match style.filter(|_| styled) {
+                        Some(CellStyle::Bold) => format!("\x1b[1m{}\x1b[0m{}", text, padding),
+                        Some(CellStyle::Colour { fg, bg }) => format!(
+                            "\x1b[38;2;{};{};{};48;2;{};{};{}m{}{}\x1b[0m",
+                            fg[0], fg[1], fg[2], bg[0], bg[1], bg[2], text, padding
+                        ),
+                        None => text + &padding,
+                    }
+                })
+                .collect();
+            format!("{}\n", cells.join(" ").trim_end())
+        };
+
+        let mut out = line(&titles, None);
+        for (r, row) in rows.iter().enumerate() {
+            out.push_str(&line(row, Some(r)));
+        }
+        if let Some(footer) = footer {
+            let rule: Vec<String> = widths.iter().map(|&width| "-".repeat(width)).collect();
+            out.push_str(&line(&rule, None));
+            out.push_str(&line(&footer, None));
+        }
+        out
+    }
+
+    fn json_rows(&self) -> Value {
+        let objects: Vec<Value> = self
+            .rows
+            .iter()
+            .map(|row| {
+                let object: Map<String, Value> = self
+                    .columns
+                    .iter()
+                    .map(|c| c.key.clone())
+                    .zip(row.iter().cloned())
+                    .collect();
+                Value::Object(object)
+            })
+            .collect();
+        Value::Array(objects)
+    }
+
+    fn render_json(&self) -> String {
+        let mut out = serde_json::to_string_pretty(&self.json_rows()).unwrap_or_default();
+        out.push('\n');
+        out
+    }
+
+    fn render_delimited(&self, delimiter: char, escape: fn(&str) -> String) -> String {
+        let mut out = String::new();
+        for row in std::iter::once(self.titles()).chain(self.text_rows()) {
+            let fields: Vec<String> = row.iter().map(|field| escape(field)).collect();
+            out.push_str(&fields.join(&delimiter.to_string()));
+            out.push('\n');
+        }
+        out
+    }
+
+    fn render_markdown(&self) -> String {
+        let escape = |s: &str| s.replace('|', "\\|").replace('\n', " ");
+        let mut out = String::new();
+        let titles: Vec<String> = self.titles().iter().map(|t| escape(t)).collect();
+        out.push_str(&format!("| {} |\n", titles.join(" | ")));
+        out.push_str(&format!("|{}\n", " --- |".repeat(titles.len())));
+        for (r, row) in self.text_rows().iter().enumerate() {
+            let cells: Vec<String> = row
+                .iter()
+                .enumerate()
+                .map(|(c, cell)| match escape(cell) {
+                    text if !text.is_empty()
+                        && self.styles.get(&(r, c)) == Some(&CellStyle::Bold) =>
+                    {
+                        format!("**{}**", text)
+                    }
+                    text => text,
+                })
+                .collect();
+            out.push_str(&format!("| {} |\n", cells.join(" | ")));
+        }
+        if let Some(footer) = &self.footer {
+            let cells: Vec<String> = footer.iter().map(|cell| escape(&cell_text(cell))).collect();
+            out.push_str(&format!("| {} |\n", cells.join(" | ")));
+        }
+        out
+    }
+}
+
+/// Renders several tables as `(key, title, table)` sections: a JSON object
+/// of row arrays by key, or each table under its title in other formats. A
+/// single section renders exactly like its table.
+pub fn render_sections(sections: &[(&str, &str, &Table)], format: OutputFormat) -> String {
+    if let [(_, _, table)] = sections {
+        return table.render(format);
+    }
+    if format == OutputFormat::Json {
+        let object: Map<String, Value> = sections
+            .iter()
+            .map(|(key, _, table)| (key.to_string(), table.json_rows()))
+            .collect();
+        let mut out = serde_json::to_string_pretty(&object).unwrap_or_default();
+        out.push('\n');
+        return out;
+    }
+    sections
+        .iter()
+        .map(|(_, title, table)| match format {
+            OutputFormat::Markdown => format!("## {}\n\n{}", title, table.render(format)),
+            _ => format!("{}\n{}", title, table.render(format)),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub fn print_sections(sections: &[(&str, &str, &Table)], format: OutputFormat) {
+    print!("{}", render_sections(sections, format));
+}
+
+/// The time zone and strftime format that times are shown in.
+#[derive(Clone, Debug)]
+pub struct TimeFormat {
+    pub tz: Tz,
+    pub format: String,
+}
+
+impl TimeFormat {
+    pub const DEFAULT_FORMAT: &str = "%Y-%m-%d %H:%M %Z";
+
+    /// Formats an API timestamp, passing anything unparseable through as is.
+    pub fn format(&self, datetime_str: &str) -> String {
+        match datetime_str.parse::<DateTime<Utc>>() {
+            Ok(dt) => dt.with_timezone(&self.tz).format(&self.format).to_string(),
+            Err(_) => datetime_str.to_string(),
+        }
+    }
+}
+
+/// The system's IANA time zone, or UTC when it cannot be determined.
+pub fn system_time_zone() -> Tz {
+    iana_time_zone::get_timezone()
+        .ok()
+        .and_then(|name| name.parse().ok())
+        .unwrap_or(Tz::UTC)
+}
+
+/// Parses an IANA time zone name such as `Europe/London`.
+pub fn parse_time_zone(name: &str) -> Result<Tz, String> {
+    name.parse().map_err(|_| {
+        format!(
+            "unknown time zone `{}`; use an IANA name such as Europe/London",
+            name
+        )
+    })
+}
+
+/// Checks a strftime format up front, since formatting with an invalid one
+/// would fail on every timestamp.
+pub fn parse_date_format(format: &str) -> Result<String, String> {
+    if StrftimeItems::new(format).any(|item| item == Item::Error) {
+        return Err(format!("invalid date format `{}`", format));
+    }
+    Ok(format.to_string())
+}
+
+/// Whether stdout is a terminal that should get ANSI styles.
+pub fn use_styles() -> bool {
+    std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none()
+}
+
+pub fn terminal_width() -> Option<usize> {
+    std::env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.parse().ok())
+        .or_else(|| terminal_size::terminal_size().map(|(width, _)| width.0 as usize))
+}
+
+/// Cuts `s` to at most `width` display cells, marking the cut with an ellipsis.
+fn truncate(s: &str, width: usize) -> String {
+    if s.width() <= width {
+        return s.to_string();
+    }
+    let mut out = String::new();
+    let mut used = 0;
+    for c in s.chars() {
+        let w = c.width().unwrap_or(0);
+        if used + w + 1 > width {
+            break;
+        }
+        out.push(c);
+        used += w;
+    }
+    if width > 0 {
+        out.push('…');
+    }
+    out
+}
+
+fn cell_text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        Value::Bool(true) => "Y".to_string(),
+        Value::Bool(false) => "N".to_string(),
+        other => other.to_string(),
+    }
+}
+
+fn csv_escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn tsv_escape(field: &str) -> String {
+    field.replace(['\t', '\n', '\r'], " ")
+}
+
+/// Parses numeric strings such as `selected_by_percent` so that JSON output
+/// carries numbers, falling back to the original string.
+pub fn number_or_string(s: &str) -> Value {
+    s.parse::<f64>()
+        .ok()
+        .and_then(serde_json::Number::from_f64)
+        .map_or_else(|| Value::String(s.to_string()), Value::Number)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn sample() -> Table {
+        let mut table = Table::new(&[("id", "ID"), ("name", "Name"), ("news", "News")]);
+        table.push(vec![json!(5), json!("Gabriel"), json!("Thigh injury, 75%")]);
+        table.push(vec![json!(430), json!("Haaland"), Value::Null]);
+        table
+    }
+
+    #[test]
+    fn test_render_table() {
+        assert_eq!(
+            sample().render_table(None, false),
+            "ID  Name    News\n5   Gabriel Thigh injury, 75%\n430 Haaland\n"
+        );
+    }
+
+    #[test]
+    fn test_render_table_aligns_by_display_width() {
+        let mut table = Table::new(&[("name", "Name"), ("points", "Pts")]);
+        table.push(vec![json!("Gündoğan"), json!(1)]);
+        table.push(vec![json!("Kovac\u{30c}ic\u{301}"), json!(2)]);
+        table.push(vec![json!("三笘"), json!(3)]);
+        assert_eq!(
+            table.render_table(None, false),
+            "Name     Pts\nGündoğan 1\nKovac\u{30c}ic\u{301}  2\n三笘     3\n"
+        );
+    }
+
+    #[test]
+    fn test_render_table_truncates() {
+        let table = sample().max_width("news", 10);
+        assert_eq!(
+            table.render_table(None, false),
+            "ID  Name    News\n5   Gabriel Thigh inj…\n430 Haaland\n"
+        );
+        assert_eq!(
+            sample().render_table(Some(26), false),
+            "ID  Name    News\n5   Gabriel Thigh injury,…\n430 Haaland\n"
+        );
+    }
+
+    #[test]
+    fn test_truncate_wide_characters() {
+        assert_eq!(truncate("日本語テキスト", 6), "日本…");
+        assert_eq!(truncate("Dúbravka", 8), "Dúbravka");
+        assert_eq!(truncate("Dúbravka", 5), "Dúbr…");
+    }
+
+    #[test]
+    fn test_render_json() {
+        let value: Value = serde_json::from_str(&sample().render(OutputFormat::Json)).unwrap();
+        assert_eq!(value[0]["news"], json!("Thigh injury, 75%"));
+        assert_eq!(
+            value[1],
+            json!({"id": 430, "name": "Haaland", "news": null})
+        );
+    }
+
+    #[test]
+    fn test_render_csv_and_tsv() {
+        assert_eq!(
+            sample().render(OutputFormat::Csv),
+            "ID,Name,News\n5,Gabriel,\"Thigh injury, 75%\"\n430,Haaland,\n"
+        );
+        assert_eq!(
+            sample().render(OutputFormat::Tsv),
+            "ID\tName\tNews\n5\tGabriel\tThigh injury, 75%\n430\tHaaland\t\n"
+        );
+    }
+
+    #[test]
+    fn test_render_markdown() {
+        assert_eq!(
+            sample().render(OutputFormat::Markdown),
+            "| ID | Name | News |\n| --- | --- | --- |\n| 5 | Gabriel | Thigh injury, 75% |\n| 430 | Haaland |  |\n"
+        );
+    }
+
+    #[test]
+    fn test_footer() {
+        let mut table = Table::new(&[("round", "GW"), ("points", "Pts")]);
+        table.push(vec![json!(1), json!(2)]);
+        table.push(vec![json!(2), json!(13)]);
+        table.footer(vec![json!("Total"), json!(15)]);
+        assert_eq!(
+            table.render_table(None, false),
+            "GW    Pts\n1     2\n2     13\n----- ---\nTotal 15\n"
+        );
+        assert!(
+            table
+                .render(OutputFormat::Markdown)
+                .ends_with("| 2 | 13 |\n| Total | 15 |\n")
+        );
+        assert_eq!(table.render(OutputFormat::Csv), "GW,Pts\n1,2\n2,13\n");
+    }
+
+    #[test]
+    fn test_highlight() {
+        let mut table = sample();
+        table.highlight(1, 1);
+        assert_eq!(
+            table.render_table(None, true),
+            "ID  Name    News\n5   Gabriel Thigh injury, 75%\n430 \x1b[1mHaaland\x1b[0m\n"
+        );
+        assert!(
+            table
+                .render(OutputFormat::Markdown)
+                .ends_with("| 430 | **Haaland** |  |\n")
+        );
+
+        let colour = CellStyle::Colour {
+            fg: [0, 0, 0],
+            bg: [1, 252, 122],
+        };
+        table.style(0, 0, colour);
+        assert!(
+            table
+                .render_table(None, true)
+                .contains("\n\x1b[38;2;0;0;0;48;2;1;252;122m5  \x1b[0m Gabriel")
+        );
+        assert!(
+            table
+                .render(OutputFormat::Markdown)
+                .contains("| 5 | Gabriel |")
+        );
+    }
+
+    #[test]
+    fn test_number_or_string() {
+        assert_eq!(number_or_string("34.1"), json!(34.1));
+        assert_eq!(number_or_string("n/a"), json!("n/a"));
+    }
+
+    #[test]
+    fn test_time_format() {
+        let time = TimeFormat {
+            tz: "Asia/Tokyo".parse().unwrap(),
+            format: TimeFormat::DEFAULT_FORMAT.to_string(),
+        };
+        assert_eq!(time.format("2025-08-15T17:30:00Z"), "2025-08-16 02:30 JST");
+        assert_eq!(time.format("soon"), "soon");
+        let time = TimeFormat {
+            tz: "Europe/London".parse().unwrap(),
+            format: parse_date_format("%a %d %b %H:%M").unwrap(),
+        };
+        assert_eq!(time.format("2025-08-15T17:30:00Z"), "Fri 15 Aug 18:30");
+        assert!(parse_date_format("%Y-%Q").is_err());
+        assert!(parse_time_zone("Mars/Olympus").is_err());
+    }
+
+    #[test]
+    fn test_render_sections() {
+        let table = sample();
+        let sections = [("first", "First", &table), ("second", "Second", &table)];
+        let value: Value =
+            serde_json::from_str(&render_sections(&sections, OutputFormat::Json)).unwrap();
+        assert_eq!(value["second"], value["first"]);
+        assert!(value["first"].is_array());
+
+        let markdown = render_sections(&sections, OutputFormat::Markdown);
+        assert!(markdown.starts_with("## First\n\n| "));
+        assert!(markdown.contains("\n\n## Second\n\n| "));
+        assert_eq!(
+            render_sections(&sections[..1], OutputFormat::Csv),
+            table.render(OutputFormat::Csv)
+        );
+    }
+}
@@ -0,0 +1,252 @@
+//! Accent-insensitive fuzzy matching of player names.
+
+use std::cmp::Reverse;
+
+use unicode_normalization::UnicodeNormalization;
+use unicode_normalization::char::is_combining_mark;
+
+use crate::error::FplError;
+use crate::models::Element;
+
+/// Scores at or above this are treated as a deliberate match of a whole
+/// name or the start of a word, so a single best candidate is accepted.
+const CONFIDENT_SCORE: u32 = 80;
+
+/// How many candidates an ambiguous lookup reports.
+const MAX_CANDIDATES: usize = 8;
+
+/// Lowercases `s` and strips accents, so "Gündoğan" becomes "gundogan".
+pub fn fold(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.nfkd().filter(|c| !is_combining_mark(*c)) {
+        match c {
+            'ø' | 'Ø' => out.push('o'),
+            'æ' | 'Æ' => out.push_str("ae"),
+            'ß' => out.push_str("ss"),
+            'đ' | 'Đ' | 'ð' => out.push('d'),
+            'ł' | 'Ł' => out.push('l'),
+            'ı' => out.push('i'),
+            '’' => out.push('\''),
+            c => out.extend(c.to_lowercase()),
+        }
+    }
+    out
+}
+
+fn words(s: &str) -> impl Iterator<Item = &str> {
+    s.split(|c: char| c.is_whitespace() || matches!(c, '-' | '\'' | '.'))
+        .filter(|w| !w.is_empty())
+}
+
+/// Edit distance counting an adjacent transposition as one edit, so
+/// "halaand" is one typo away from "haaland".
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+fn is_subsequence(query: &str, name: &str) -> bool {
+    let mut name = name.chars();
+    query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .all(|q| name.any(|c| c == q))
+}
+
+/// Scores how well a folded `query` matches a folded `name`, from exact
+/// matches down to typos and abbreviations.
+fn score(query: &str, name: &str) -> Option<u32> {
+    if name.is_empty() {
+        return None;
+    }
+    if name == query {
+        return Some(100);
+    }
+    if name.starts_with(query) {
+        return Some(90);
+    }
+    if words(name).any(|w| w.starts_with(query)) {
+        return Some(80);
+    }
+    if words(query).all(|q| words(name).any(|w| w.starts_with(q))) {
+        return Some(75);
+    }
+    if name.contains(query) {
+        return Some(70);
+    }
+    let length = query.chars().count();
+    let allowed = length / 4;
+    if allowed > 0 {
+        let distance = std::iter::once(name)
+            .chain(words(name))
+            .map(|w| edit_distance(query, w))
+            .min()
+            .unwrap_or(usize::MAX);
+        if distance <= allowed {
+            return Some(50 - 10 * distance.min(4) as u32);
+        }
+    }
+    if length >= 3 && is_subsequence(query, name) {
+        return Some(20);
+    }
+    None
+}
+
+fn player_score(query: &str, player: &Element) -> Option<u32> {
+    let full_name = format!("{} {}", player.first_name, player.second_name);
+    [
+        &player.web_name,
+        &player.first_name,
+        &player.second_name,
+        &full_name,
+    ]
+    .iter()
+    .filter_map(|name| score(query, &fold(name)))
+    .max()
+}
+
+/// Players matching `query` on their web, first, second or full name,
+/// best matches first and ties broken by total points.
+pub fn search_players<'a>(elements: &'a [Element], query: &str) -> Vec<(&'a Element, u32)> {
+    let query = fold(query.trim());
+    if query.is_empty() {
+        return Vec::new();
+    }
+    let mut matches: Vec<(&Element, u32)> = elements
+        .iter()
+        .filter_map(|player| player_score(&query, player).map(|score| (player, score)))
+        .collect();
+    matches
+        .sort_by_key(|(player, score)| (Reverse(*score), Reverse(player.total_points), player.id));
+    matches
+}
+
+/// Resolves a player ID or name to a single player.
+///
+/// A name resolves when it is the only match, or when exactly one player
+/// matches it best as a whole name or word prefix.
+pub fn resolve_player<'a>(elements: &'a [Element], query: &str) -> Result<&'a Element, FplError> {
+    if let Ok(id) = query.trim().parse::<u64>() {
+        return elements
+            .iter()
+            .find(|player| player.id == id)
+            .ok_or_else(|| FplError::UnknownPlayer(query.to_string()));
+    }
+
+    let matches = search_players(elements, query);
+    match matches.as_slice() {
+        [] => Err(FplError::UnknownPlayer(query.to_string())),
+        [(player, _)] => Ok(player),
+        [(player, best), (_, second), ..] if *best >= CONFIDENT_SCORE && best > second => {
+            Ok(player)
+        }
+        _ => Err(FplError::AmbiguousPlayer {
+            query: query.to_string(),
+            candidates: matches
+                .iter()
+                .take(MAX_CANDIDATES)
+                .map(|(player, _)| {
+                    format!(
+                        "{} ({} {}, ID {})",
+                        player.web_name, player.first_name, player.second_name, player.id
+                    )
+                })
+                .collect(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    fn player(id: u64, web_name: &str, first_name: &str, second_name: &str) -> Element {
+        let mut value: Value =
+            serde_json::from_str(include_str!("../tests/data/element.json")).unwrap();
+        value["id"] = id.into();
+        value["web_name"] = web_name.into();
+        value["first_name"] = first_name.into();
+        value["second_name"] = second_name.into();
+        value["total_points"] = (100 - id).into();
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn players() -> Vec<Element> {
+        vec![
+            player(1, "Haaland", "Erling", "Haaland"),
+            player(2, "Gündoğan", "İlkay", "Gündoğan"),
+            player(3, "Martinez", "Damián Emiliano", "Martínez Romero"),
+            player(4, "Lisandro", "Lisandro", "Martínez"),
+            player(5, "M.Salah", "Mohamed", "Salah"),
+            player(6, "Ødegaard", "Martin", "Ødegaard"),
+        ]
+    }
+
+    #[test]
+    fn test_fold() {
+        assert_eq!(fold("Gündoğan"), "gundogan");
+        assert_eq!(fold("Ødegaard"), "odegaard");
+        assert_eq!(fold("O’Reilly"), "o'reilly");
+    }
+
+    #[test]
+    fn test_search_players() {
+        let players = players();
+        let ids = |query: &str| -> Vec<u64> {
+            search_players(&players, query)
+                .iter()
+                .map(|(p, _)| p.id)
+                .collect()
+        };
+        assert_eq!(ids("gundogan")[0], 2);
+        assert_eq!(ids("odegaard")[0], 6);
+        assert_eq!(ids("salah")[0], 5);
+        assert_eq!(ids("mo salah")[0], 5);
+        assert_eq!(ids("halaand")[0], 1);
+        assert_eq!(ids("martinez"), vec![3, 4, 6]);
+        assert!(ids("zzz").is_empty());
+    }
+
+    #[test]
+    fn test_resolve_player() {
+        let players = players();
+        assert_eq!(resolve_player(&players, "5").unwrap().id, 5);
+        assert_eq!(resolve_player(&players, "haaland").unwrap().id, 1);
+        assert_eq!(resolve_player(&players, "Gundo").unwrap().id, 2);
+        assert_eq!(resolve_player(&players, "lisandro").unwrap().id, 4);
+        assert!(matches!(
+            resolve_player(&players, "99"),
+            Err(FplError::UnknownPlayer(_))
+        ));
+        match resolve_player(&players, "martinez") {
+            Err(FplError::AmbiguousPlayer { candidates, .. }) => {
+                assert_eq!(candidates.len(), 3);
+                assert_eq!(
+                    candidates[0],
+                    "Martinez (Damián Emiliano Martínez Romero, ID 3)"
+                );
+            }
+            other => panic!("expected an ambiguous match, got {:?}", other.map(|p| p.id)),
+        }
+    }
+}